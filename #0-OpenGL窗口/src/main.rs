@@ -21,11 +21,16 @@ impl MyWindow {
 
     fn draw(&mut self) {
         let mut target = self.display.draw();
-        target.clear_color(0.1, 0.1, 0.1, 1.0);
+        render(&mut target);
         target.finish().unwrap();
     }
 }
 
+/// Draws the lesson's frame, also used by the golden tests.
+pub(crate) fn render<S: Surface>(target: &mut S) {
+    target.clear_color(0.1, 0.1, 0.1, 1.0);
+}
+
 impl ApplicationHandler for MyWindow {
     fn resumed(&mut self, _event_loop: &glium::winit::event_loop::ActiveEventLoop) {}
 
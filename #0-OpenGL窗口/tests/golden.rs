@@ -0,0 +1,16 @@
+//! Renders the lesson offscreen and compares it with its golden, see `common::golden`.
+
+use common::{golden, headless};
+
+#[path = "../src/main.rs"]
+#[allow(dead_code)]
+mod window;
+
+const GOLDENS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/goldens");
+
+#[test]
+fn window_matches_golden() {
+    let (width, height) = golden::SIZE;
+    let frame = headless::render_frame(width, height, |_, target| window::render(target)).unwrap();
+    golden::assert_golden(GOLDENS, "window", &frame);
+}
@@ -1,6 +1,6 @@
 use glium::{
     Display, Surface,
-    backend::glutin::SimpleWindowBuilder,
+    backend::{Facade, glutin::SimpleWindowBuilder},
     glutin::surface::WindowSurface,
     implement_vertex,
     winit::{
@@ -37,66 +37,67 @@ impl MyWindow {
 
     fn draw(&mut self) {
         let mut target = self.display.draw();
-        target.clear_color(0.1, 0.1, 0.1, 1.0);
-
-        const VERTEX: [Vertex; 3] = [
-            Vertex::new(0.5, -0.5, [1.0, 0.0, 0.0]),
-            Vertex::new(-0.5, -0.5, [0.0, 1.0, 0.0]),
-            Vertex::new(0.0, 0.5, [0.0, 0.0, 1.0]),
-        ];
-        const INDICES: [u16; 3] = [0, 1, 2];
-
-        let vertex_buffer = glium::VertexBuffer::new(&self.display, &VERTEX).unwrap();
-        let indices = glium::IndexBuffer::new(
-            &self.display,
-            glium::index::PrimitiveType::TrianglesList,
-            &INDICES,
-        )
-        .unwrap();
-        let program = glium::Program::from_source(
-            &self.display,
-            "
-            #version 330
-
-            in float x;
-            in float y;
-            in vec3 color;
-
-            out vec3 v_color;
-
-            void main() {
-                v_color = color;
-                gl_Position = vec4(x, y, 0.0, 1.0);
-            }
-            ",
-            "
-            #version 330
-
-            in vec3 v_color;
-
-            out vec4 color;
-
-            void main() {
-                color = vec4(v_color, 1.0);
-            }
-            ",
-            None,
-        )
-        .unwrap();
-        target
-            .draw(
-                &vertex_buffer,
-                &indices,
-                &program,
-                &glium::uniforms::EmptyUniforms,
-                &Default::default(),
-            )
-            .unwrap();
-
+        render(&self.display, &mut target);
         target.finish().unwrap();
     }
 }
 
+/// Draws the lesson's frame, also used by the golden tests.
+pub(crate) fn render<F: Facade, S: Surface>(facade: &F, target: &mut S) {
+    target.clear_color(0.1, 0.1, 0.1, 1.0);
+
+    const VERTEX: [Vertex; 3] = [
+        Vertex::new(0.5, -0.5, [1.0, 0.0, 0.0]),
+        Vertex::new(-0.5, -0.5, [0.0, 1.0, 0.0]),
+        Vertex::new(0.0, 0.5, [0.0, 0.0, 1.0]),
+    ];
+    const INDICES: [u16; 3] = [0, 1, 2];
+
+    let vertex_buffer = glium::VertexBuffer::new(facade, &VERTEX).unwrap();
+    let indices =
+        glium::IndexBuffer::new(facade, glium::index::PrimitiveType::TrianglesList, &INDICES)
+            .unwrap();
+    let program = glium::Program::from_source(
+        facade,
+        "
+        #version 330
+
+        in float x;
+        in float y;
+        in vec3 color;
+
+        out vec3 v_color;
+
+        void main() {
+            v_color = color;
+            gl_Position = vec4(x, y, 0.0, 1.0);
+        }
+        ",
+        "
+        #version 330
+
+        in vec3 v_color;
+
+        out vec4 color;
+
+        void main() {
+            color = vec4(v_color, 1.0);
+        }
+        ",
+        None,
+    )
+    .unwrap();
+    target
+        .draw(
+            &vertex_buffer,
+            &indices,
+            &program,
+            &glium::uniforms::EmptyUniforms,
+            &Default::default(),
+        )
+        .unwrap();
+}
+
 impl ApplicationHandler for MyWindow {
     fn resumed(&mut self, _event_loop: &glium::winit::event_loop::ActiveEventLoop) {}
 
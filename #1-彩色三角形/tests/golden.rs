@@ -0,0 +1,48 @@
+//! Renders every scene of the lesson offscreen and compares it with its golden, see
+//! `common::golden`.
+
+use common::{golden, headless};
+
+#[path = "../src/main.rs"]
+#[allow(dead_code)]
+mod triangle;
+
+#[path = "../src/bin/colorful.rs"]
+#[allow(dead_code)]
+mod colorful;
+
+#[path = "../src/bin/rectangle.rs"]
+#[allow(dead_code)]
+mod rectangle;
+
+const GOLDENS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/goldens");
+
+#[test]
+fn triangle_matches_golden() {
+    let (width, height) = golden::SIZE;
+    let frame = headless::render_frame(width, height, |facade, target| {
+        triangle::render(facade, target)
+    })
+    .unwrap();
+    golden::assert_golden(GOLDENS, "triangle", &frame);
+}
+
+#[test]
+fn colorful_matches_golden() {
+    let (width, height) = golden::SIZE;
+    let frame = headless::render_frame(width, height, |facade, target| {
+        colorful::render(facade, target)
+    })
+    .unwrap();
+    golden::assert_golden(GOLDENS, "colorful", &frame);
+}
+
+#[test]
+fn rectangle_matches_golden() {
+    let (width, height) = golden::SIZE;
+    let frame = headless::render_frame(width, height, |facade, target| {
+        rectangle::render(facade, target)
+    })
+    .unwrap();
+    golden::assert_golden(GOLDENS, "rectangle", &frame);
+}
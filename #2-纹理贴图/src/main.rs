@@ -1,17 +1,18 @@
+use std::rc::Rc;
+
+use common::{
+    ColorSpace, SamplerDescriptor, Texture, TextureLoader, create_display, linear_program,
+};
 use glium::{
-    Display, Surface, Texture2d,
-    backend::glutin::SimpleWindowBuilder,
+    Display, Surface,
+    backend::Facade,
     glutin::surface::WindowSurface,
-    implement_vertex,
-    texture::RawImage2d,
-    uniform,
-    uniforms::Sampler,
+    implement_vertex, uniform,
     winit::{
         application::ApplicationHandler, event::WindowEvent, event_loop::EventLoopBuilder,
         window::Window,
     },
 };
-use image::GenericImageView;
 
 #[derive(Clone, Copy)]
 struct Vertex {
@@ -35,11 +36,11 @@ impl Vertex {
 struct MyWindow {
     window: Window,
     display: Display<WindowSurface>,
-    texture: Texture2d,
+    texture: Rc<Texture>,
 }
 
 impl MyWindow {
-    fn new(window: Window, display: Display<WindowSurface>, texture: Texture2d) -> Self {
+    fn new(window: Window, display: Display<WindowSurface>, texture: Rc<Texture>) -> Self {
         Self {
             window,
             display,
@@ -49,46 +50,56 @@ impl MyWindow {
 
     fn draw(&mut self) {
         let mut target = self.display.draw();
-        target.clear_color(0.1, 0.1, 0.1, 1.0);
-
-        const VERTEX: [Vertex; 3] = [
-            Vertex::new(0.5, -0.5, [1.0, 1.0]),
-            Vertex::new(-0.5, -0.5, [0.0, 1.0]),
-            Vertex::new(0.0, 0.5, [0.5, 0.0]),
-        ];
-        const INDICES: [u16; 3] = [0, 1, 2];
-
-        let vertex_buffer = glium::VertexBuffer::new(&self.display, &VERTEX).unwrap();
-        let indices = glium::IndexBuffer::new(
-            &self.display,
-            glium::index::PrimitiveType::TrianglesList,
-            &INDICES,
-        )
-        .unwrap();
+        render(&self.display, &mut target, &self.texture);
+        target.finish().unwrap();
+    }
+}
+
+/// Draws the lesson's frame, also used by the golden tests.
+pub(crate) fn render<F: Facade, S: Surface>(facade: &F, target: &mut S, texture: &Texture) {
+    target.clear_color(0.1, 0.1, 0.1, 1.0);
 
-        let program = glium::Program::from_source(
-            &self.display,
-            include_str!("./shaders/texture.vert"),
-            include_str!("./shaders/texture.frag"),
-            None,
+    const VERTEX: [Vertex; 3] = [
+        Vertex::new(0.5, -0.5, [1.0, 0.0]),
+        Vertex::new(-0.5, -0.5, [0.0, 0.0]),
+        Vertex::new(0.0, 0.5, [0.5, 1.0]),
+    ];
+    const INDICES: [u16; 3] = [0, 1, 2];
+
+    let vertex_buffer = glium::VertexBuffer::new(facade, &VERTEX).unwrap();
+    let indices =
+        glium::IndexBuffer::new(facade, glium::index::PrimitiveType::TrianglesList, &INDICES)
+            .unwrap();
+
+    let program = linear_program(
+        facade,
+        include_str!("./shaders/texture.vert"),
+        include_str!("./shaders/texture.frag"),
+    )
+    .unwrap();
+    let tex = SamplerDescriptor::default().sampler(texture);
+    let uniforms = uniform! {
+        tex: tex,
+    };
+    target
+        .draw(
+            &vertex_buffer,
+            &indices,
+            &program,
+            &uniforms,
+            &Default::default(),
         )
         .unwrap();
-        let tex = Sampler::new(&self.texture);
-        let uniforms = uniform! {
-            tex: tex,
-        };
-        target
-            .draw(
-                &vertex_buffer,
-                &indices,
-                &program,
-                &uniforms,
-                &Default::default(),
-            )
-            .unwrap();
+}
 
-        target.finish().unwrap();
-    }
+/// The image is sRGB-encoded color, decoded to linear when sampled. Falls back to a
+/// checkerboard when the image is missing rather than panicking.
+pub(crate) fn load_texture<F: Facade>(facade: &F) -> Rc<Texture> {
+    TextureLoader::new(env!("CARGO_MANIFEST_DIR")).load_or_missing(
+        facade,
+        "textures/木质纹理.jpg",
+        ColorSpace::Srgb,
+    )
 }
 
 impl ApplicationHandler for MyWindow {
@@ -112,15 +123,9 @@ impl ApplicationHandler for MyWindow {
 
 fn main() {
     let event_loop = EventLoopBuilder::<()>::default().build().unwrap();
-    let (window, display) = SimpleWindowBuilder::new().build(&event_loop);
+    let (window, display) = create_display(&event_loop, "texture").unwrap();
 
-    let image = image::ImageReader::open("./textures/木质纹理.jpg")
-        .unwrap()
-        .decode()
-        .unwrap();
-    let dimensions = image.dimensions();
-    let image = image.to_rgba8().into_vec();
-    let texture = Texture2d::new(&display, RawImage2d::from_raw_rgba(image, dimensions)).unwrap();
+    let texture = load_texture(&display);
 
     let mut app = MyWindow::new(window, display, texture);
     event_loop.run_app(&mut app).unwrap();
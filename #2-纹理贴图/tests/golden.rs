@@ -0,0 +1,34 @@
+//! Renders every scene of the lesson offscreen into an sRGB target, like the window's, and
+//! compares it with its golden, see `common::golden`.
+
+use common::{golden, headless};
+
+#[path = "../src/main.rs"]
+#[allow(dead_code)]
+mod texture;
+
+#[path = "../src/bin/diamond.rs"]
+#[allow(dead_code)]
+mod diamond;
+
+const GOLDENS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/goldens");
+
+#[test]
+fn texture_matches_golden() {
+    let (width, height) = golden::SIZE;
+    let frame = headless::render_srgb_frame(width, height, |facade, target| {
+        texture::render(facade, target, &texture::load_texture(facade))
+    })
+    .unwrap();
+    golden::assert_golden(GOLDENS, "texture", &frame);
+}
+
+#[test]
+fn diamond_matches_golden() {
+    let (width, height) = golden::SIZE;
+    let frame = headless::render_srgb_frame(width, height, |facade, target| {
+        diamond::render(facade, target, &diamond::load_texture(facade))
+    })
+    .unwrap();
+    golden::assert_golden(GOLDENS, "diamond", &frame);
+}
@@ -1,12 +1,6 @@
-use glium::{
-    Display, Surface as _,
-    backend::glutin::SimpleWindowBuilder,
-    glutin::surface::WindowSurface,
-    implement_vertex,
-    winit::{event_loop::EventLoopBuilder, window::Window},
-};
+use glium::{Surface, backend::Facade, implement_vertex, winit::event_loop::EventLoopBuilder};
 use mats::radian;
-use transform::{Drawable, MyWindow};
+use transform::{Drawable, FrameContext, MyWindow, Projection, Resources, create_display};
 
 #[derive(Clone, Copy)]
 struct Vertex {
@@ -23,13 +17,13 @@ impl Vertex {
 
 implement_vertex!(Vertex, x, y, z);
 
-struct Canvas {}
+#[derive(Default)]
+pub(crate) struct Canvas {
+    projection: Projection,
+}
 
 impl Drawable for Canvas {
-    fn draw(&mut self, window: &Window, display: &Display<WindowSurface>) {
-        let mut target = display.draw();
-        target.clear_color(0.1, 0.1, 0.1, 1.0);
-
+    fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
         const VERTEX: [Vertex; 8] = [
             Vertex::new(1.0, 1.0, 1.0),
             Vertex::new(1.0, 1.0, -1.0),
@@ -49,30 +43,34 @@ impl Drawable for Canvas {
             6, 7, 2, 7, 2, 3, // bottom
         ];
 
-        let vertex_buffer = glium::VertexBuffer::new(display, &VERTEX).unwrap();
-        let indices = glium::IndexBuffer::new(
-            display,
-            glium::index::PrimitiveType::TrianglesList,
-            &INDICES,
-        )
-        .unwrap();
+        resources
+            .load_vertex_buffer(facade, "cube", &VERTEX)
+            .unwrap();
+        resources
+            .load_index_buffer(
+                facade,
+                "cube",
+                glium::index::PrimitiveType::TrianglesList,
+                &INDICES,
+            )
+            .unwrap();
+        resources
+            .load_program_files(
+                facade,
+                "shader",
+                transform::shader!("src/shaders/shader.vert"),
+                transform::shader!("src/shaders/shader.frag"),
+            )
+            .unwrap();
+    }
 
-        let program = glium::Program::from_source(
-            display,
-            include_str!("../shaders/shader.vert"),
-            include_str!("../shaders/shader.frag"),
-            None,
-        )
-        .unwrap();
+    fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext) {
+        target.clear_color(0.1, 0.1, 0.1, 1.0);
 
         let transform = mats::rotate3(radian(45.0), [1.0, 1.0, 1.0].into());
         let view = mats::translate3([0.0, 0.0, -5.0].into());
-        let pre = mats::perspective(
-            45.0,
-            window.inner_size().width as f32 / window.inner_size().height as f32,
-            0.1,
-            100.0,
-        );
+        self.projection.set_aspect(context.aspect());
+        let pre = self.projection.matrix();
         let transform = pre * view * transform;
 
         let uniforms = glium::uniform! {
@@ -81,23 +79,21 @@ impl Drawable for Canvas {
 
         target
             .draw(
-                &vertex_buffer,
-                &indices,
-                &program,
+                context.resources.vertex_buffer("cube"),
+                context.resources.index_buffer("cube"),
+                context.resources.program("shader"),
                 &uniforms,
                 &Default::default(),
             )
             .unwrap();
-
-        target.finish().unwrap();
     }
 }
 
 fn main() {
     let event_loop = EventLoopBuilder::<()>::default().build().unwrap();
 
-    let (window, display) = SimpleWindowBuilder::new().build(&event_loop);
+    let (window, display) = create_display(&event_loop, "cube").unwrap();
 
-    let mut app = MyWindow::new(Canvas {}, window, display);
+    let mut app = MyWindow::new(Canvas::default(), window, display);
     event_loop.run_app(&mut app).unwrap();
 }
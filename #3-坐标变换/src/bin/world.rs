@@ -1,140 +1,94 @@
-use std::f32;
-
-use glium::{
-    Display, DrawParameters, Surface as _, Texture2d,
-    backend::glutin::SimpleWindowBuilder,
-    glutin::surface::WindowSurface,
-    implement_vertex,
-    texture::RawImage2d,
-    uniforms::Sampler,
-    winit::{event_loop::EventLoopBuilder, window::Window},
-};
-use image::GenericImageView;
+use glium::{Surface, backend::Facade, winit::event_loop::EventLoopBuilder};
 use mats::radian;
-use transform::{Drawable, MyWindow};
+use transform::{
+    Drawable, FrameContext, MyWindow, Projection, RenderState, Resources, create_display,
+    lighting::{Lighting, SpotLight},
+    primitives,
+};
 
-#[derive(Clone, Copy)]
-struct Vertex {
-    x: f32,
-    y: f32,
-    z: f32,
-    tex_coord: (f32, f32),
+pub(crate) struct Canvas {
+    projection: Projection,
+    lighting: Lighting,
 }
 
-impl Vertex {
-    const fn new(x: f32, y: f32, z: f32) -> Self {
+impl Default for Canvas {
+    fn default() -> Self {
+        let mut lighting = Lighting::default();
+        lighting.spots.push(
+            SpotLight::new([2.0, 2.0, 2.0], [-1.0, -1.0, -1.0], 8.0)
+                .with_color([1.0, 0.9, 0.7], 6.0),
+        );
         Self {
-            x,
-            y,
-            z,
-            tex_coord: (0.0, 0.0),
+            projection: Projection::default(),
+            lighting,
         }
     }
 }
 
-implement_vertex!(Vertex, x, y, z, tex_coord);
-
-struct Canvas {
-    texture: Texture2d,
-}
-
 impl Drawable for Canvas {
-    fn draw(&mut self, window: &Window, display: &Display<WindowSurface>) {
-        let mut target = display.draw();
-        target.clear_color_and_depth((0.1, 0.1, 0.1, 1.0), -f32::INFINITY);
-        const VERTEX: [Vertex; 8] = [
-            Vertex::new(1.0, 1.0, 1.0),
-            Vertex::new(1.0, 1.0, -1.0),
-            Vertex::new(1.0, -1.0, 1.0),
-            Vertex::new(1.0, -1.0, -1.0),
-            Vertex::new(-1.0, 1.0, 1.0),
-            Vertex::new(-1.0, 1.0, -1.0),
-            Vertex::new(-1.0, -1.0, 1.0),
-            Vertex::new(-1.0, -1.0, -1.0),
-        ];
-        const INDICES: [u16; 36] = [
-            4, 2, 0, 4, 6, 2, // front
-            1, 7, 5, 1, 3, 7, // back
-            0, 3, 1, 0, 2, 3, // right
-            5, 6, 4, 5, 7, 6, // left
-            5, 0, 1, 5, 4, 0, // top
-            2, 7, 6, 2, 3, 7, // bottom
-        ];
+    fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
+        let mesh = primitives::cube(2.0);
 
-        let vertex = INDICES
-            .into_iter()
-            .enumerate()
-            .map(|(i, item)| {
-                let mut v = VERTEX[item as usize];
-                match i % 6 {
-                    0 => v.tex_coord = (0.0, 0.0),
-                    1 => v.tex_coord = (1.0, 1.0),
-                    2 => v.tex_coord = (1.0, 0.0),
-                    3 => v.tex_coord = (0.0, 0.0),
-                    4 => v.tex_coord = (0.0, 1.0),
-                    5 => v.tex_coord = (1.0, 1.0),
-                    _ => unreachable!(),
-                }
-                v
-            })
-            .collect::<Vec<_>>();
+        resources
+            .load_vertex_buffer(facade, "cube", &mesh.vertices())
+            .unwrap();
+        resources
+            .load_index_buffer(
+                facade,
+                "cube",
+                glium::index::PrimitiveType::TrianglesList,
+                &mesh.indices,
+            )
+            .unwrap();
+        resources
+            .load_program_files(
+                facade,
+                "lit",
+                transform::shader!("src/shaders/lit.vert"),
+                transform::shader!("src/shaders/lit.frag"),
+            )
+            .unwrap();
 
-        let vertex_buffer = glium::VertexBuffer::new(display, &vertex).unwrap();
-        let indices = glium::IndexBuffer::new(
-            display,
-            glium::index::PrimitiveType::TrianglesList,
-            &(0..36).into_iter().collect::<Vec<u16>>(),
-        )
-        .unwrap();
+        resources.load_texture(facade, "stone", "textures/石墙纹理.jpg");
+    }
 
-        let program = glium::Program::from_source(
-            display,
-            include_str!("../shaders/box.vert"),
-            include_str!("../shaders/box.frag"),
-            None,
-        )
-        .unwrap();
+    fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext) {
+        let state = RenderState::opaque(self.projection.depth_convention());
+        state.clear(target, (0.1, 0.1, 0.1, 1.0));
 
-        let transform = mats::rotate3(radian(45.0), [1.0, 1.0, 1.0].into());
+        let model = mats::rotate3(radian(45.0), [1.0, 1.0, 1.0].into());
         let view = mats::translate3([0.0, 0.0, -5.0].into());
-        let pre = mats::perspective(
-            45.0,
-            window.inner_size().width as f32 / window.inner_size().height as f32,
-            0.1,
-            100.0,
-        );
-        let transform = pre * view * transform;
+        self.projection.set_aspect(context.aspect());
+        let pre = self.projection.matrix();
+        let transform = pre * view * model;
 
-        let tex = Sampler::new(&self.texture);
+        let tex = context.resources.sampler("stone");
 
-        let uniforms = glium::uniform! {
+        let uniforms = self.lighting.with(glium::uniform! {
             transform: transform,
+            model: model,
+            view: view,
+            shininess: 32.0f32,
             tex: tex,
-        };
+        });
 
-        let mut param = DrawParameters::default();
-        param.depth.write = true;
-        param.depth.test = glium::DepthTest::IfMore;
         target
-            .draw(&vertex_buffer, &indices, &program, &uniforms, &param)
+            .draw(
+                context.resources.vertex_buffer("cube"),
+                context.resources.index_buffer("cube"),
+                context.resources.program("lit"),
+                &uniforms,
+                &state.draw_parameters(),
+            )
             .unwrap();
-
-        target.finish().unwrap();
     }
 }
 
 fn main() {
     let event_loop = EventLoopBuilder::<()>::default().build().unwrap();
 
-    let (window, display) = SimpleWindowBuilder::new().build(&event_loop);
-    let image = image::ImageReader::open("./textures/石墙纹理.jpg")
-        .unwrap()
-        .decode()
-        .unwrap();
-    let dimensions = image.dimensions();
-    let image = image.to_rgba8().into_vec();
-    let texture = Texture2d::new(&display, RawImage2d::from_raw_rgba(image, dimensions)).unwrap();
+    let (window, display) = create_display(&event_loop, "world").unwrap();
 
-    let mut app = MyWindow::new(Canvas { texture }, window, display);
+    let mut app = MyWindow::new(Canvas::default(), window, display);
     event_loop.run_app(&mut app).unwrap();
 }
@@ -1,5 +1,8 @@
 mod vertex;
-mod window;
 
-pub use vertex::Vertex;
-pub use window::{MyWindow, Drawable};
\ No newline at end of file
+pub use common::{
+    DepthConvention, Drawable, FrameContext, Mesh, MeshVertex, MyWindow, Offscreen,
+    PreprocessError, Preprocessor, Projection, ProjectionKind, RenderState, RenderStateError,
+    Resources, ShaderError, ShaderFile, create_display, lighting, primitives, shader,
+};
+pub use vertex::Vertex;
\ No newline at end of file
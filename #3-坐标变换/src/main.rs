@@ -1,69 +1,73 @@
-use glium::{
-    Display, Surface as _,
-    backend::glutin::SimpleWindowBuilder,
-    glutin::surface::WindowSurface,
-    winit::{event_loop::EventLoopBuilder, window::Window},
-};
-use transform::{Drawable, MyWindow, Vertex};
+use glium::{Surface, backend::Facade, winit::event_loop::EventLoopBuilder};
+use transform::{Drawable, FrameContext, MyWindow, Projection, Resources, Vertex, create_display};
 
-struct Canvas {}
+pub(crate) struct Canvas {
+    projection: Projection,
+}
 
-impl Drawable for Canvas {
-    fn draw(&mut self, window: &Window, display: &Display<WindowSurface>) {
-        let mut target = display.draw();
-        target.clear_color(0.1, 0.1, 0.1, 1.0);
+impl Canvas {
+    pub(crate) fn new() -> Self {
+        Self {
+            projection: Projection::orthographic(2.0, -1.0, 1.0),
+        }
+    }
+}
 
+impl Drawable for Canvas {
+    fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
         const VERTEX: [Vertex; 3] = [
             Vertex::new(0.5, -0.5),
             Vertex::new(-0.5, -0.5),
             Vertex::new(0.0, 0.5),
         ];
         const INDICES: [u16; 3] = [0, 1, 2];
-        let vertex_buffer = glium::VertexBuffer::new(display, &VERTEX).unwrap();
-        let indices = glium::IndexBuffer::new(
-            display,
-            glium::index::PrimitiveType::TrianglesList,
-            &INDICES,
-        )
-        .unwrap();
-        let program = glium::Program::from_source(
-            display,
-            include_str!("./shaders/shader.vert"),
-            include_str!("./shaders/shader.frag"),
-            None,
-        )
-        .unwrap();
+        resources
+            .load_vertex_buffer(facade, "triangle", &VERTEX)
+            .unwrap();
+        resources
+            .load_index_buffer(
+                facade,
+                "triangle",
+                glium::index::PrimitiveType::TrianglesList,
+                &INDICES,
+            )
+            .unwrap();
+        resources
+            .load_program_files(
+                facade,
+                "shader",
+                transform::shader!("src/shaders/shader.vert"),
+                transform::shader!("src/shaders/shader.frag"),
+            )
+            .unwrap();
+    }
+
+    fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext) {
+        target.clear_color(0.1, 0.1, 0.1, 1.0);
 
         let transform = mats::rotate3_z(mats::radian(45.0));
 
-        let p = window.inner_size();
-        let pro = if p.width > p.height {
-            mats::scale3([p.height as f32 / p.width as f32, 1.0, 1.0].into())
-        } else {
-            mats::scale3([1.0, p.width as f32 / p.height as f32, 1.0].into())
-        };
-        let transform = pro * transform;
+        self.projection.set_aspect(context.aspect());
+        let transform = self.projection.matrix() * transform;
 
         let uniforms = glium::uniform! {
             transform: transform,
         };
         target
             .draw(
-                &vertex_buffer,
-                &indices,
-                &program,
+                context.resources.vertex_buffer("triangle"),
+                context.resources.index_buffer("triangle"),
+                context.resources.program("shader"),
                 &uniforms,
                 &Default::default(),
             )
             .unwrap();
-
-        target.finish().unwrap();
     }
 }
 
 fn main() {
     let event_loop = EventLoopBuilder::<()>::default().build().unwrap();
-    let (window, display) = SimpleWindowBuilder::new().build(&event_loop);
-    let mut app = MyWindow::new(Canvas {}, window, display);
+    let (window, display) = create_display(&event_loop, "transform").unwrap();
+    let mut app = MyWindow::new(Canvas::new(), window, display);
     event_loop.run_app(&mut app).unwrap();
 }
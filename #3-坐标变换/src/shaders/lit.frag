@@ -0,0 +1,41 @@
+#version 330
+
+out vec4 color;
+in vec3 frag_position;
+in vec3 frag_normal;
+in vec2 frag_tex_coord;
+flat in vec3 frag_eye;
+
+uniform sampler2D tex;
+
+#include "lighting.glsl"
+
+void main() {
+    vec4 albedo = texture(tex, frag_tex_coord);
+    vec3 normal = normalize(frag_normal);
+    vec3 to_eye = normalize(frag_eye - frag_position);
+    vec3 result = ambient * albedo.rgb;
+
+    for (int i = 0; i < directional_count; i++) {
+        DirectionalLight light = directional_lights[i];
+        result += blinn_phong(albedo.rgb, normal, to_eye, normalize(-light.direction), light.color);
+    }
+    for (int i = 0; i < point_count; i++) {
+        PointLight light = point_lights[i];
+        vec3 to_light = light.position - frag_position;
+        float distance = length(to_light);
+        vec3 radiance = light.color * attenuation(distance, light.range);
+        result += blinn_phong(albedo.rgb, normal, to_eye, to_light / distance, radiance);
+    }
+    for (int i = 0; i < spot_count; i++) {
+        SpotLight light = spot_lights[i];
+        vec3 to_light = light.position - frag_position;
+        float distance = length(to_light);
+        float cone = smoothstep(light.outer_cos, light.inner_cos,
+                                dot(-to_light / distance, normalize(light.direction)));
+        vec3 radiance = light.color * attenuation(distance, light.range) * cone;
+        result += blinn_phong(albedo.rgb, normal, to_eye, to_light / distance, radiance);
+    }
+
+    color = vec4(result, albedo.a);
+}
\ No newline at end of file
@@ -0,0 +1,23 @@
+#version 330
+
+#include "projection.glsl"
+
+in vec3 position;
+in vec3 normal;
+in vec2 tex_coord;
+
+out vec3 frag_position;
+out vec3 frag_normal;
+out vec2 frag_tex_coord;
+flat out vec3 frag_eye;
+
+uniform mat4 model;
+uniform mat4 view;
+
+void main() {
+    gl_Position = project(position);
+    frag_position = vec3(model * vec4(position, 1.0));
+    frag_normal = mat3(transpose(inverse(model))) * normal;
+    frag_tex_coord = tex_coord;
+    frag_eye = inverse(view)[3].xyz;
+}
\ No newline at end of file
@@ -1,11 +1,10 @@
 #version 330
 
+#include "projection.glsl"
+
 in float x;
 in float y;
-in float z;
-
-uniform mat4 transform;
 
 void main() {
-    gl_Position = transform * vec4(x, y, z, 1.0);
+    gl_Position = project(vec3(x, y, 0.0));
 }
\ No newline at end of file
@@ -0,0 +1,66 @@
+//! Renders every scene of the crate offscreen and compares it with its golden, see
+//! `common::golden`.
+
+use common::golden::{self, SIZE};
+use transform::{Drawable, Offscreen};
+
+#[path = "../src/main.rs"]
+#[allow(dead_code)]
+mod transform_scene;
+
+#[path = "../src/bin/3d.rs"]
+#[allow(dead_code)]
+mod three_d;
+
+#[path = "../src/bin/box.rs"]
+#[allow(dead_code)]
+mod box_scene;
+
+#[path = "../src/bin/cube.rs"]
+#[allow(dead_code)]
+mod cube;
+
+#[path = "../src/bin/cube_line.rs"]
+#[allow(dead_code)]
+mod cube_line;
+
+#[path = "../src/bin/world.rs"]
+#[allow(dead_code)]
+mod world;
+
+const GOLDENS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/goldens");
+
+fn check<T: Drawable>(name: &str, scene: T) {
+    let mut offscreen = Offscreen::new(scene, SIZE.0, SIZE.1).unwrap();
+    golden::assert_golden(GOLDENS, name, &offscreen.render());
+}
+
+#[test]
+fn transform_matches_golden() {
+    check("transform", transform_scene::Canvas::new());
+}
+
+#[test]
+fn three_d_matches_golden() {
+    check("3d", three_d::Canvas::default());
+}
+
+#[test]
+fn box_matches_golden() {
+    check("box", box_scene::Canvas::default());
+}
+
+#[test]
+fn cube_matches_golden() {
+    check("cube", cube::Canvas::default());
+}
+
+#[test]
+fn cube_line_matches_golden() {
+    check("cube_line", cube_line::Canvas::default());
+}
+
+#[test]
+fn world_matches_golden() {
+    check("world", world::Canvas::default());
+}
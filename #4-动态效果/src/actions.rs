@@ -0,0 +1,296 @@
+use std::{collections::HashMap, fmt, path::Path};
+
+use glium::winit::{
+    event::{ElementState, KeyEvent, MouseButton, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+use serde::Deserialize;
+
+use crate::{InputState, LookSettings};
+
+/// A physical input that can trigger an action.
+///
+/// In TOML a binding is written as `{ key = "KeyW" }` or `{ mouse = "Left" }`, using the
+/// variant names of winit's `KeyCode` and `MouseButton`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Binding {
+    Key(KeyCode),
+    Mouse(MouseButton),
+}
+
+impl Binding {
+    fn is_pressed(&self, input: &InputState) -> bool {
+        match *self {
+            Self::Key(code) => input.is_pressed(code),
+            Self::Mouse(button) => input.is_button_pressed(button),
+        }
+    }
+
+    fn just_pressed(&self, input: &InputState) -> bool {
+        match *self {
+            Self::Key(code) => input.just_pressed(code),
+            Self::Mouse(button) => input.button_just_pressed(button),
+        }
+    }
+
+    fn just_released(&self, input: &InputState) -> bool {
+        match *self {
+            Self::Key(code) => input.just_released(code),
+            Self::Mouse(button) => input.button_just_released(button),
+        }
+    }
+
+    fn state_in(&self, event: &WindowEvent) -> Option<ElementState> {
+        match (*self, event) {
+            (
+                Self::Key(code),
+                WindowEvent::KeyboardInput {
+                    event:
+                        KeyEvent {
+                            physical_key: PhysicalKey::Code(pressed),
+                            state,
+                            repeat: false,
+                            ..
+                        },
+                    ..
+                },
+            ) if code == *pressed => Some(*state),
+            (
+                Self::Mouse(button),
+                WindowEvent::MouseInput {
+                    state,
+                    button: pressed,
+                    ..
+                },
+            ) if button == *pressed => Some(*state),
+            _ => None,
+        }
+    }
+}
+
+/// A continuous input read once per frame, see [`AnalogBinding`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Deserialize)]
+pub enum AnalogSource {
+    /// Cursor movement in pixels, right is positive.
+    MouseX,
+    /// Cursor movement in pixels, down is positive.
+    MouseY,
+    /// Raw device motion, see [`InputState::raw_mouse_delta`].
+    RawMouseX,
+    RawMouseY,
+    /// Scroll in lines.
+    ScrollX,
+    ScrollY,
+}
+
+impl AnalogSource {
+    fn value(self, input: &InputState) -> f32 {
+        match self {
+            Self::MouseX => input.mouse_delta().0 as f32,
+            Self::MouseY => input.mouse_delta().1 as f32,
+            Self::RawMouseX => input.raw_mouse_delta().0 as f32,
+            Self::RawMouseY => input.raw_mouse_delta().1 as f32,
+            Self::ScrollX => input.scroll().0,
+            Self::ScrollY => input.scroll().1,
+        }
+    }
+}
+
+/// An analog input feeding an axis, scaled by `scale`. In TOML `{ source = "ScrollY" }` or
+/// `{ source = "MouseX", scale = -0.1 }`.
+#[derive(Debug, Clone, Copy, PartialEq, Deserialize)]
+pub struct AnalogBinding {
+    pub source: AnalogSource,
+    #[serde(default = "unit_scale")]
+    pub scale: f32,
+}
+
+fn unit_scale() -> f32 {
+    1.0
+}
+
+/// Two sets of bindings pulling a value towards `1.0` and `-1.0`, plus analog inputs added on
+/// top.
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct Axis {
+    #[serde(default)]
+    pub positive: Vec<Binding>,
+    #[serde(default)]
+    pub negative: Vec<Binding>,
+    #[serde(default)]
+    pub analog: Vec<AnalogBinding>,
+}
+
+/// Named actions and axes mapped to any number of bindings.
+///
+/// ```toml
+/// [look]
+/// sensitivity = 0.05
+/// invert_y = false
+///
+/// [actions]
+/// Quit = [{ key = "Escape" }]
+///
+/// [axes.MoveForward]
+/// positive = [{ key = "KeyW" }, { key = "ArrowUp" }]
+/// negative = [{ key = "KeyS" }, { key = "ArrowDown" }]
+///
+/// [axes.Zoom]
+/// analog = [{ source = "ScrollY", scale = 0.5 }]
+/// ```
+#[derive(Debug, Clone, Default, Deserialize)]
+pub struct ActionMap {
+    #[serde(default)]
+    actions: HashMap<String, Vec<Binding>>,
+    #[serde(default)]
+    axes: HashMap<String, Axis>,
+    #[serde(default)]
+    look: LookSettings,
+}
+
+#[derive(Debug)]
+pub enum ActionMapError {
+    Io(std::io::Error),
+    Parse(toml::de::Error),
+}
+
+impl fmt::Display for ActionMapError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io(err) => write!(f, "failed to read bindings: {err}"),
+            Self::Parse(err) => write!(f, "invalid bindings: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for ActionMapError {}
+
+impl ActionMap {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_toml(source: &str) -> Result<Self, ActionMapError> {
+        toml::from_str(source).map_err(ActionMapError::Parse)
+    }
+
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ActionMapError> {
+        let source = std::fs::read_to_string(path).map_err(ActionMapError::Io)?;
+        Self::from_toml(&source)
+    }
+
+    pub fn bind(&mut self, action: &str, binding: Binding) {
+        self.actions
+            .entry(action.to_owned())
+            .or_default()
+            .push(binding);
+    }
+
+    pub fn bind_axis(&mut self, axis: &str, positive: Binding, negative: Binding) {
+        let axis = self.axes.entry(axis.to_owned()).or_default();
+        axis.positive.push(positive);
+        axis.negative.push(negative);
+    }
+
+    pub fn bind_analog(&mut self, axis: &str, binding: AnalogBinding) {
+        let axis = self.axes.entry(axis.to_owned()).or_default();
+        axis.analog.push(binding);
+    }
+
+    pub fn look(&self) -> &LookSettings {
+        &self.look
+    }
+
+    pub fn set_look(&mut self, look: LookSettings) {
+        self.look = look;
+    }
+
+    pub fn bindings(&self, action: &str) -> &[Binding] {
+        self.actions.get(action).map_or(&[], Vec::as_slice)
+    }
+
+    /// Whether any binding of `action` is held.
+    pub fn is_active(&self, action: &str, input: &InputState) -> bool {
+        self.bindings(action).iter().any(|b| b.is_pressed(input))
+    }
+
+    pub fn just_activated(&self, action: &str, input: &InputState) -> bool {
+        self.bindings(action).iter().any(|b| b.just_pressed(input))
+    }
+
+    pub fn just_deactivated(&self, action: &str, input: &InputState) -> bool {
+        self.bindings(action).iter().any(|b| b.just_released(input))
+    }
+
+    /// Press or release of `action` carried by a single window event, for scenes that react
+    /// in `Drawable::handle`. Key repeats are ignored.
+    pub fn triggered_by(&self, action: &str, event: &WindowEvent) -> Option<ElementState> {
+        self.bindings(action).iter().find_map(|b| b.state_in(event))
+    }
+
+    /// Value of `axis`. The keys and buttons give `-1`, `0` or `1`, zero when both or neither
+    /// directions are held, and the analog inputs of the frame are added unclamped.
+    pub fn axis(&self, axis: &str, input: &InputState) -> f32 {
+        let Some(axis) = self.axes.get(axis) else {
+            return 0.0;
+        };
+        let positive = axis.positive.iter().any(|b| b.is_pressed(input));
+        let negative = axis.negative.iter().any(|b| b.is_pressed(input));
+        let analog: f32 = axis
+            .analog
+            .iter()
+            .map(|binding| binding.source.value(input) * binding.scale)
+            .sum();
+        positive as i32 as f32 - negative as i32 as f32 + analog
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const BINDINGS: &str = r#"
+        [axes.Zoom]
+        positive = [{ key = "Equal" }]
+        negative = [{ key = "Minus" }]
+        analog = [{ source = "ScrollY" }, { source = "MouseX", scale = 0.5 }]
+    "#;
+
+    #[test]
+    fn axis_adds_analog_inputs_to_keys() {
+        let actions = ActionMap::from_toml(BINDINGS).unwrap();
+        let mut input = InputState::new();
+        assert_eq!(actions.axis("Zoom", &input), 0.0);
+
+        input.press(KeyCode::Equal);
+        assert_eq!(actions.axis("Zoom", &input), 1.0);
+        input.press(KeyCode::Minus);
+        assert_eq!(actions.axis("Zoom", &input), 0.0);
+
+        input.scroll_by(0.0, 3.0);
+        input.move_cursor(0.0, 0.0);
+        input.move_cursor(-4.0, 0.0);
+        assert_eq!(actions.axis("Zoom", &input), 1.0);
+
+        input.end_frame();
+        input.release(KeyCode::Minus);
+        assert_eq!(actions.axis("Zoom", &input), 1.0);
+        assert_eq!(actions.axis("Unbound", &input), 0.0);
+    }
+
+    #[test]
+    fn bind_analog_matches_toml() {
+        let mut actions = ActionMap::new();
+        actions.bind_analog(
+            "Zoom",
+            AnalogBinding {
+                source: AnalogSource::ScrollY,
+                scale: 2.0,
+            },
+        );
+        let mut input = InputState::new();
+        input.scroll_by(1.0, -0.5);
+        assert_eq!(actions.axis("Zoom", &input), -1.0);
+    }
+}
@@ -1,196 +1,213 @@
-use std::{f32, time::SystemTime};
+use std::{
+    error::Error,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
 
-use animation::{Drawable, MyWindow};
-use device_query::{DeviceQuery, DeviceState};
+use animation::{
+    ActionMap, Capture, CursorGrab, Drawable, FrameContext, InputState, Material, Mesh, MeshVertex,
+    MyWindow, RenderState, Resources, SamplerDescriptor,
+    camera::{Camera, CameraInput, FpsCamera},
+    gltf_scene::GltfScene,
+    lighting::{DirectionalLight, Lighting, SpotLight},
+    obj::ObjModel,
+    primitives,
+};
 use glium::{
-    Display, DrawParameters, Surface as _, Texture2d,
-    backend::glutin::SimpleWindowBuilder,
-    glutin::surface::WindowSurface,
-    implement_vertex,
-    texture::RawImage2d,
-    uniforms::Sampler,
+    IndexBuffer, Surface, VertexBuffer,
+    backend::Facade,
     winit::{
-        dpi::PhysicalPosition,
-        event::{KeyEvent, WindowEvent},
+        event::{ElementState, WindowEvent},
         event_loop::EventLoopBuilder,
-        keyboard::{KeyCode, PhysicalKey},
         window::Window,
     },
 };
-use image::GenericImageView;
 use mats::radian;
 
-#[derive(Clone, Copy)]
-struct Vertex {
-    x: f32,
-    y: f32,
-    z: f32,
-    tex_coord: (f32, f32),
-}
-
-#[derive(Default)]
-struct Camera {
-    position: mats::Vec3<f32>,
-    yaw: f32,
-    pitch: f32,
-}
+const GRAB_CURSOR: &str = "GrabCursor";
+const RELEASE_CURSOR: &str = "ReleaseCursor";
+const QUIT: &str = "Quit";
+const SCREENSHOT: &str = "Screenshot";
+const RECORD: &str = "Record";
+const TOGGLE_SPOT_LIGHT: &str = "ToggleSpotLight";
+const LIGHT_INTENSITY: &str = "LightIntensity";
 
-impl Camera {
-    fn new() -> Self {
-        Self::default()
-    }
+/// Spot light shining down on the cube, toggled at runtime.
+fn spot_light() -> SpotLight {
+    SpotLight::new([0.0, 4.0, 0.0], [0.0, -1.0, 0.0], 10.0)
+        .with_color([0.6, 0.8, 1.0], 8.0)
+        .with_angles(20.0, 30.0)
 }
 
-impl Camera {
-    fn view(&self) -> mats::Mat4<f32> {
-        let direction = mats::Vec4::from([0.0, 0.0, -1.0, 0.0]);
-        let direction = direction * mats::rotate3_x(mats::radian(self.pitch));
-        let direction = direction * mats::rotate3_y(mats::radian(self.yaw));
-        let target = self.position + direction.xyz();
-        mats::look_at(self.position, target, [0.0, 1.0, 0.0].into())
-    }
-
-    fn handle(&mut self, epsilon: f32) {
-        let direction = mats::Vec4::from([0.0, 0.0, -1.0, 0.0]);
-        let direction = direction * mats::rotate3_y(mats::radian(self.yaw));
-        let delta_ws = direction.xyz() * epsilon;
-        let delta_ad = (direction * mats::rotate3_y(radian(90.0))).xyz() * epsilon;
+pub(crate) struct Canvas {
+    camera: FpsCamera,
+    actions: ActionMap,
+    lighting: Lighting,
+    /// Polished stone for the cube.
+    cube_material: Material,
+    /// Rough, unfiltered stone for the loaded model.
+    model_material: Material,
 
-        let state = DeviceState::new();
-        let keys = state.get_keys();
-        if keys.contains(&device_query::Keycode::W) {
-            self.position += delta_ws;
-        }
-        if keys.contains(&device_query::Keycode::S) {
-            self.position -= delta_ws;
-        }
-        if keys.contains(&device_query::Keycode::A) {
-            self.position -= delta_ad;
-        }
-        if keys.contains(&device_query::Keycode::D) {
-            self.position += delta_ad;
-        }
-        if keys.contains(&device_query::Keycode::Space) {
-            self.position[0][1] += epsilon;
-        }
-        if keys.contains(&device_query::Keycode::LShift) {
-            self.position[0][1] -= epsilon;
-        }
-    }
+    grab: CursorGrab,
+    capture: Option<Capture>,
+    /// OBJ or glTF file passed on the command line, drawn in front of the cube.
+    model: Option<PathBuf>,
+    models: Vec<(mats::Mat4<f32>, VertexBuffer<MeshVertex>, IndexBuffer<u32>)>,
 }
 
-impl Vertex {
-    const fn new(x: f32, y: f32, z: f32) -> Self {
+impl Canvas {
+    /// `model` is an OBJ or glTF file to draw in front of the cube.
+    pub(crate) fn new(model: Option<PathBuf>) -> Self {
+        let camera = FpsCamera::new([0.0, 0.0, 0.0].into());
+        let depth = camera.projection().depth_convention();
         Self {
-            x,
-            y,
-            z,
-            tex_coord: (0.0, 0.0),
+            camera,
+            lighting: Lighting {
+                directional: vec![
+                    DirectionalLight::new([1.0, -1.0, -0.5]).with_color([1.0, 0.95, 0.9], 0.8),
+                ],
+                spots: vec![spot_light()],
+                ..Lighting::default()
+            },
+            cube_material: Material::new("lit", depth)
+                .with_texture("tex", "stone")
+                .with("shininess", 64.0),
+            model_material: Material::new("lit", depth)
+                .with_sampled_texture("tex", "stone", SamplerDescriptor::nearest())
+                .with("shininess", 4.0),
+            actions: ActionMap::load(concat!(env!("CARGO_MANIFEST_DIR"), "/bindings.toml"))
+                .unwrap_or_else(|err| {
+                    eprintln!("{err}, falling back to the default bindings");
+                    ActionMap::from_toml(include_str!("../../bindings.toml")).unwrap()
+                }),
+            grab: CursorGrab::new(),
+            capture: None,
+            model,
+            models: Vec::new(),
         }
     }
 }
 
-implement_vertex!(Vertex, x, y, z, tex_coord);
+/// A mesh of a model with its transform relative to the model origin.
+type Instance = (mats::Mat4<f32>, Mesh);
 
-struct Canvas {
-    texture: Texture2d,
-    time: SystemTime,
-    dt: SystemTime,
-    camera: Camera,
-
-    cursor_lock: bool,
+fn load_model(path: &Path) -> Result<Vec<Instance>, Box<dyn Error>> {
+    let extension = path.extension().and_then(|ext| ext.to_str());
+    if let Some("gltf" | "glb") = extension {
+        let scene = GltfScene::load(path)?;
+        Ok(scene
+            .instances()
+            .into_iter()
+            .map(|(world, primitive)| (world, primitive.mesh.clone()))
+            .collect())
+    } else {
+        let model = ObjModel::load(path)?;
+        Ok(vec![(mats::scale3([1.0, 1.0, 1.0].into()), model.merged())])
+    }
 }
 
 impl Drawable for Canvas {
-    fn draw(&mut self, window: &Window, display: &Display<WindowSurface>) {
-        let mut target = display.draw();
-        target.clear_color_and_depth((0.1, 0.1, 0.1, 1.0), f32::INFINITY);
-        const VERTEX: [Vertex; 8] = [
-            Vertex::new(1.0, 1.0, 1.0),
-            Vertex::new(1.0, 1.0, -1.0),
-            Vertex::new(1.0, -1.0, 1.0),
-            Vertex::new(1.0, -1.0, -1.0),
-            Vertex::new(-1.0, 1.0, 1.0),
-            Vertex::new(-1.0, 1.0, -1.0),
-            Vertex::new(-1.0, -1.0, 1.0),
-            Vertex::new(-1.0, -1.0, -1.0),
-        ];
-        const INDICES: [u16; 36] = [
-            4, 2, 0, 4, 6, 2, // front
-            1, 7, 5, 1, 3, 7, // back
-            0, 3, 1, 0, 2, 3, // right
-            5, 6, 4, 5, 7, 6, // left
-            5, 0, 1, 5, 4, 0, // top
-            2, 7, 6, 2, 3, 7, // bottom
-        ];
-
-        let vertex = INDICES
-            .into_iter()
-            .enumerate()
-            .map(|(i, item)| {
-                let mut v = VERTEX[item as usize];
-                match i % 6 {
-                    0 => v.tex_coord = (0.0, 0.0),
-                    1 => v.tex_coord = (1.0, 1.0),
-                    2 => v.tex_coord = (1.0, 0.0),
-                    3 => v.tex_coord = (0.0, 0.0),
-                    4 => v.tex_coord = (0.0, 1.0),
-                    5 => v.tex_coord = (1.0, 1.0),
-                    _ => unreachable!(),
+    fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
+        if let Some(path) = &self.model {
+            match load_model(path) {
+                Ok(instances) => {
+                    for (world, mut mesh) in instances {
+                        if mesh.normals.is_empty() {
+                            mesh.compute_normals();
+                        }
+                        self.models.push((
+                            world,
+                            mesh.vertex_buffer(facade).unwrap(),
+                            mesh.index_buffer(facade).unwrap(),
+                        ));
+                    }
                 }
-                v
-            })
-            .collect::<Vec<_>>();
-
-        let vertex_buffer = glium::VertexBuffer::new(display, &vertex).unwrap();
-        let indices = glium::IndexBuffer::new(
-            display,
-            glium::index::PrimitiveType::TrianglesList,
-            &(0..36).into_iter().collect::<Vec<u16>>(),
-        )
-        .unwrap();
+                Err(err) => eprintln!("{err}"),
+            }
+        }
 
-        let program = glium::Program::from_source(
-            display,
-            include_str!("../shaders/box.vert"),
-            include_str!("../shaders/box.frag"),
-            None,
-        )
-        .unwrap();
+        let mesh = primitives::cube(2.0);
 
-        let time = SystemTime::now()
-            .duration_since(self.time)
-            .unwrap()
-            .as_secs_f32()
-            * 30.0;
-        let transform = mats::rotate3(radian(time), [1.0, 1.0, 1.0].into());
-        let view = self.camera.view();
-        let pre = mats::perspective(
-            45.0,
-            window.inner_size().width as f32 / window.inner_size().height as f32,
-            0.1,
-            100.0,
-        );
-        let transform = pre * view * transform;
+        resources
+            .load_vertex_buffer(facade, "cube", &mesh.vertices())
+            .unwrap();
+        resources
+            .load_index_buffer(
+                facade,
+                "cube",
+                glium::index::PrimitiveType::TrianglesList,
+                &mesh.indices,
+            )
+            .unwrap();
+        resources
+            .load_program_files(
+                facade,
+                "lit",
+                animation::shader!("src/shaders/lit.vert"),
+                animation::shader!("src/shaders/lit.frag"),
+            )
+            .unwrap();
 
-        let tex = Sampler::new(&self.texture);
+        resources.load_texture(facade, "stone", "textures/石墙纹理.jpg");
+    }
 
-        let uniforms = glium::uniform! {
-            transform: transform,
-            tex: tex,
+    fn update(&mut self, dt: Duration, input: &InputState) {
+        let input = CameraInput {
+            actions: &self.actions,
+            input,
+            mouse_look: self.grab.is_grabbed(),
         };
+        self.camera.update(dt, &input);
+
+        if self.actions.just_activated(TOGGLE_SPOT_LIGHT, input.input) {
+            if self.lighting.spots.is_empty() {
+                self.lighting.spots.push(spot_light());
+            } else {
+                self.lighting.spots.clear();
+            }
+        }
+        let scale = 1.0 + self.actions.axis(LIGHT_INTENSITY, input.input) * dt.as_secs_f32();
+        for light in &mut self.lighting.directional {
+            light.intensity = (light.intensity * scale).clamp(0.0, 10.0);
+        }
+    }
+
+    fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext) {
+        let state = RenderState::opaque(self.camera.projection().depth_convention());
+        state.clear(target, (0.1, 0.1, 0.1, 1.0));
+
+        let time = context.elapsed.as_secs_f32() * 30.0;
+        let model = mats::rotate3(radian(time), [1.0, 1.0, 1.0].into());
+        let view = self.camera.view(context.alpha);
+        let pre = self.camera.projection().matrix();
+        let transform = pre * view * model;
 
-        let mut param = DrawParameters::default();
-        param.depth.write = true;
-        param.depth.test = glium::DepthTest::IfLess;
-        target
-            .draw(&vertex_buffer, &indices, &program, &uniforms, &param)
+        let uniforms = self.lighting.with(glium::uniform! {
+            transform: transform,
+            model: model,
+            view: view,
+        });
+        self.cube_material
+            .draw(
+                target,
+                context.resources,
+                context.resources.vertex_buffer("cube"),
+                context.resources.index_buffer("cube"),
+                uniforms,
+            )
             .unwrap();
 
-        target.finish().unwrap();
-        self.camera
-            .handle(self.dt.elapsed().unwrap().as_secs_f32() * 2.0);
-        self.dt = SystemTime::now();
+        for (world, vertices, indices) in &self.models {
+            let model = mats::translate3([0.0, 0.0, -5.0].into()) * *world;
+            let uniforms = self.lighting.with(glium::uniform! {
+                transform: pre * view * model,
+                model: model,
+                view: view,
+            });
+            self.model_material
+                .draw(target, context.resources, vertices, indices, uniforms)
+                .unwrap();
+        }
     }
 
     fn handle(
@@ -200,76 +217,51 @@ impl Drawable for Canvas {
         _window_id: glium::winit::window::WindowId,
         event: glium::winit::event::WindowEvent,
     ) {
-        let state = DeviceState::new();
-        let keys = state.get_keys();
-        let old = self.cursor_lock;
-        self.cursor_lock = !keys.contains(&device_query::Keycode::LAlt);
-        if self.cursor_lock != old {
-            if self.cursor_lock {
-                window.set_cursor_visible(false);
-            } else {
-                window.set_cursor_visible(true);
-            }
+        self.camera.projection_mut().handle_event(&event);
+        match event {
+            WindowEvent::Focused(true) => self.grab.grab(window),
+            WindowEvent::Focused(false) => self.grab.release(window),
+            _ => {}
         }
-        let cx = window.inner_size().width as i32 / 2;
-        let cy = window.inner_size().height as i32 / 2;
-        if self.cursor_lock {
-            if let WindowEvent::CursorMoved {
-                position: PhysicalPosition { x, y },
-                ..
-            } = event
-            {
-                let coords = (x as i32, y as i32);
-                let epsilon = 0.05;
-                let (x, y) = coords;
-                let (dx, dy) = (x - cx, y - cy);
-                self.camera.yaw += dx as f32 * epsilon;
-                self.camera.pitch += dy as f32 * epsilon;
-                self.camera.pitch = self.camera.pitch.max(-89.9).min(89.9);
-                self.camera.yaw = self.camera.yaw.rem_euclid(360.0);
-            }
+        if let Some(ElementState::Pressed) = self.actions.triggered_by(GRAB_CURSOR, &event) {
+            self.grab.grab(window);
         }
-        if self.cursor_lock {
-            window
-                .set_cursor_position(PhysicalPosition { x: cx, y: cy })
-                .unwrap();
+        if let Some(ElementState::Pressed) = self.actions.triggered_by(RELEASE_CURSOR, &event) {
+            self.grab.release(window);
         }
-        if let WindowEvent::KeyboardInput {
-            event:
-                KeyEvent {
-                    physical_key: PhysicalKey::Code(KeyCode::Escape),
-                    ..
-                },
-            ..
-        } = event
-        {
+        if let Some(ElementState::Pressed) = self.actions.triggered_by(QUIT, &event) {
             event_loop.exit();
         }
+        if let Some(ElementState::Pressed) = self.actions.triggered_by(SCREENSHOT, &event) {
+            let time = SystemTime::now()
+                .duration_since(SystemTime::UNIX_EPOCH)
+                .unwrap()
+                .as_millis();
+            self.capture = Some(Capture::Screenshot(format!("screenshot-{time}.png").into()));
+        }
+        if let Some(ElementState::Pressed) = self.actions.triggered_by(RECORD, &event) {
+            self.capture = Some(Capture::Sequence {
+                dir: "frames".into(),
+                frames: 120,
+            });
+        }
+    }
+
+    fn capture(&mut self) -> Option<Capture> {
+        self.capture.take()
     }
 }
 
 fn main() {
     let event_loop = EventLoopBuilder::<()>::default().build().unwrap();
 
-    let (window, display) = SimpleWindowBuilder::new().build(&event_loop);
-    let image = image::ImageReader::open("./textures/石墙纹理.jpg")
-        .unwrap()
-        .decode()
-        .unwrap();
-    let dimensions = image.dimensions();
-    let image = image.to_rgba8().into_vec();
-    let texture = Texture2d::new(&display, RawImage2d::from_raw_rgba(image, dimensions)).unwrap();
+    let (window, display) = animation::create_display(&event_loop, "camera").unwrap();
 
-    let mut app = MyWindow::new(
-        Canvas {
-            camera: Camera::new(),
-            texture,
-            time: SystemTime::now(),
-            dt: SystemTime::now(),
-            cursor_lock: false,
-        },
-        window,
-        display,
-    );
+    // The first argument that is not a flag like `--hot-reload`.
+    let model = std::env::args()
+        .skip(1)
+        .find(|arg| !arg.starts_with("--"))
+        .map(PathBuf::from);
+    let mut app = MyWindow::new(Canvas::new(model), window, display);
     event_loop.run_app(&mut app).unwrap();
 }
@@ -0,0 +1,196 @@
+use std::time::Duration;
+
+use animation::{
+    Drawable, FrameContext, InputState, Material, MyWindow, Projection, RenderState, Resources,
+    SamplerDescriptor, Transform,
+    ecs::{Behavior, Entity, MeshRef, World},
+    lighting::Lighting,
+    primitives,
+};
+use glium::{
+    Surface,
+    backend::Facade,
+    winit::{
+        event::WindowEvent,
+        event_loop::{ActiveEventLoop, EventLoopBuilder},
+        window::{Window, WindowId},
+    },
+};
+use mats::radian;
+
+const GRID: i32 = 20;
+
+/// Spins the entity around its own axis.
+struct Spin {
+    axis: [f32; 3],
+    degrees_per_second: f32,
+}
+
+impl Behavior for Spin {
+    fn update(&mut self, entity: Entity, world: &mut World, dt: Duration, _input: &InputState) {
+        if let Some(transform) = world.get_mut::<Transform>(entity) {
+            transform.rotate(self.axis, self.degrees_per_second * dt.as_secs_f32());
+        }
+    }
+}
+
+/// Vertical bobbing, driven by a system.
+struct Bob {
+    height: f32,
+    phase: f32,
+}
+
+pub(crate) struct Canvas {
+    projection: Projection,
+    lighting: Lighting,
+    world: World,
+}
+
+impl Canvas {
+    pub(crate) fn new() -> Self {
+        let projection = Projection::default();
+        let depth = projection.depth_convention();
+        // Same mesh data and program, different looks.
+        let polished = Material::new("lit", depth)
+            .with_texture("tex", "stone")
+            .with("shininess", 64.0);
+        let rough = Material::new("lit", depth)
+            .with_sampled_texture("tex", "stone", SamplerDescriptor::nearest())
+            .with("shininess", 4.0);
+        let painted = Material::new("lit_untextured", depth)
+            .with("base_color", [0.8, 0.35, 0.2])
+            .with("shininess", 16.0);
+
+        let mut world = World::new();
+        for x in 0..GRID {
+            for z in 0..GRID {
+                let entity = world.spawn();
+                let position = [
+                    (x - GRID / 2) as f32 * 2.0,
+                    0.0,
+                    (z - GRID / 2) as f32 * 2.0,
+                ];
+                world.insert(
+                    entity,
+                    Transform::from_translation(position).with_scale([0.5; 3]),
+                );
+                let (mesh, material) = match (x + z) % 3 {
+                    0 => ("sphere", &painted),
+                    1 => ("cube", &rough),
+                    _ => ("cube", &polished),
+                };
+                world.insert(entity, MeshRef(mesh));
+                world.insert(entity, material.clone());
+                world.insert(
+                    entity,
+                    Bob {
+                        height: 0.5,
+                        phase: (x * 7 + z * 13) as f32 * 0.3,
+                    },
+                );
+                world.add_behavior(
+                    entity,
+                    Spin {
+                        axis: [1.0, 1.0, 0.0],
+                        degrees_per_second: 30.0 + ((x * z) % 7) as f32 * 20.0,
+                    },
+                );
+            }
+        }
+
+        let mut time = 0.0;
+        world.add_system(
+            move |world: &mut World, dt: Duration, _input: &InputState| {
+                time += dt.as_secs_f32();
+                let bobs = world
+                    .query::<Bob>()
+                    .map(|(entity, bob)| (entity, bob.height * (time * 2.0 + bob.phase).sin()))
+                    .collect::<Vec<_>>();
+                for (entity, y) in bobs {
+                    if let Some(transform) = world.get_mut::<Transform>(entity) {
+                        transform.translation[1] = y;
+                    }
+                }
+            },
+        );
+
+        Self {
+            projection,
+            lighting: Lighting::default(),
+            world,
+        }
+    }
+}
+
+impl Drawable for Canvas {
+    fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
+        for (name, mesh) in [
+            ("sphere", primitives::icosphere(1.0, 2)),
+            ("cube", primitives::cube(2.0)),
+        ] {
+            resources
+                .load_vertex_buffer(facade, name, &mesh.vertices())
+                .unwrap();
+            resources
+                .load_index_buffer(
+                    facade,
+                    name,
+                    glium::index::PrimitiveType::TrianglesList,
+                    &mesh.indices,
+                )
+                .unwrap();
+        }
+        resources
+            .load_program_files(
+                facade,
+                "lit",
+                animation::shader!("src/shaders/lit.vert"),
+                animation::shader!("src/shaders/lit.frag"),
+            )
+            .unwrap();
+        resources
+            .load_program_variant(
+                facade,
+                "lit_untextured",
+                animation::shader!("src/shaders/lit.vert"),
+                animation::shader!("src/shaders/lit.frag"),
+                &[("UNTEXTURED", "1")],
+            )
+            .unwrap();
+
+        resources.load_texture(facade, "stone", "textures/石墙纹理.jpg");
+    }
+
+    fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext) {
+        let state = RenderState::opaque(self.projection.depth_convention());
+        state.clear(target, (0.1, 0.1, 0.1, 1.0));
+
+        let view = mats::translate3([0.0, -2.0, -40.0].into()) * mats::rotate3_x(radian(30.0));
+        let view_projection = self.projection.matrix() * view;
+        let shared = self.lighting.with(glium::uniform! { view: view });
+        self.world.draw(target, context, view_projection, &shared);
+    }
+
+    fn handle(
+        &mut self,
+        _window: &Window,
+        _event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        self.projection.handle_event(&event);
+    }
+
+    fn world(&mut self) -> Option<&mut World> {
+        Some(&mut self.world)
+    }
+}
+
+fn main() {
+    let event_loop = EventLoopBuilder::<()>::default().build().unwrap();
+
+    let (window, display) = animation::create_display(&event_loop, "crowd").unwrap();
+
+    let mut app = MyWindow::new(Canvas::new(), window, display);
+    event_loop.run_app(&mut app).unwrap();
+}
@@ -0,0 +1,148 @@
+use std::time::Duration;
+
+use animation::{
+    Drawable, FrameContext, InputState, MyWindow, Projection, RenderState, Resources, Transform,
+    primitives,
+    scene::{NodeId, SceneGraph},
+};
+use glium::{
+    Surface,
+    backend::Facade,
+    winit::{
+        event::WindowEvent,
+        event_loop::{ActiveEventLoop, EventLoopBuilder},
+        window::{Window, WindowId},
+    },
+};
+use mats::radian;
+
+const UP: [f32; 3] = [0.0, 1.0, 0.0];
+
+pub(crate) struct Canvas {
+    projection: Projection,
+    graph: SceneGraph<&'static str>,
+    sun: NodeId,
+    earth_orbit: NodeId,
+    earth: NodeId,
+    moon_orbit: NodeId,
+}
+
+impl Canvas {
+    pub(crate) fn new() -> Self {
+        let mut graph = SceneGraph::new();
+        let sun = graph.add(None, Transform::new().with_scale([1.5; 3]), Some("sphere"));
+        // Orbits are empty pivots so the planets' own spin and scale don't carry over.
+        let earth_orbit = graph.add(None, Transform::new(), None);
+        let earth = graph.add(
+            Some(earth_orbit),
+            Transform::from_translation([4.0, 0.0, 0.0]).with_scale([0.5; 3]),
+            Some("sphere"),
+        );
+        let moon_orbit = graph.add(
+            Some(earth_orbit),
+            Transform::from_translation([4.0, 0.0, 0.0]),
+            None,
+        );
+        graph.add(
+            Some(moon_orbit),
+            Transform::from_translation([1.0, 0.0, 0.0]).with_scale([0.2; 3]),
+            Some("cube"),
+        );
+
+        Self {
+            projection: Projection::default(),
+            graph,
+            sun,
+            earth_orbit,
+            earth,
+            moon_orbit,
+        }
+    }
+}
+
+impl Drawable for Canvas {
+    fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
+        for (name, mesh) in [
+            ("sphere", primitives::uv_sphere(1.0, 32, 16)),
+            ("cube", primitives::cube(1.0)),
+        ] {
+            resources
+                .load_vertex_buffer(facade, name, &mesh.vertices())
+                .unwrap();
+            resources
+                .load_index_buffer(
+                    facade,
+                    name,
+                    glium::index::PrimitiveType::TrianglesList,
+                    &mesh.indices,
+                )
+                .unwrap();
+        }
+        resources
+            .load_program_files(
+                facade,
+                "box",
+                animation::shader!("src/shaders/box.vert"),
+                animation::shader!("src/shaders/box.frag"),
+            )
+            .unwrap();
+
+        resources.load_texture(facade, "stone", "textures/石墙纹理.jpg");
+    }
+
+    fn update(&mut self, dt: Duration, _input: &InputState) {
+        let dt = dt.as_secs_f32();
+        self.graph.transform_mut(self.sun).rotate(UP, 10.0 * dt);
+        self.graph
+            .transform_mut(self.earth_orbit)
+            .rotate(UP, 20.0 * dt);
+        self.graph.transform_mut(self.earth).rotate(UP, 90.0 * dt);
+        self.graph
+            .transform_mut(self.moon_orbit)
+            .rotate(UP, 120.0 * dt);
+    }
+
+    fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext) {
+        let state = RenderState::opaque(self.projection.depth_convention());
+        state.clear(target, (0.1, 0.1, 0.1, 1.0));
+
+        let view = mats::translate3([0.0, 0.0, -12.0].into()) * mats::rotate3_x(radian(25.0));
+        let pre = self.projection.matrix();
+        let tex = context.resources.sampler("stone");
+
+        self.graph.traverse(|_, world, &mesh| {
+            let uniforms = glium::uniform! {
+                transform: pre * view * world,
+                tex: tex,
+            };
+            target
+                .draw(
+                    context.resources.vertex_buffer(mesh),
+                    context.resources.index_buffer(mesh),
+                    context.resources.program("box"),
+                    &uniforms,
+                    &state.draw_parameters(),
+                )
+                .unwrap();
+        });
+    }
+
+    fn handle(
+        &mut self,
+        _window: &Window,
+        _event_loop: &ActiveEventLoop,
+        _window_id: WindowId,
+        event: WindowEvent,
+    ) {
+        self.projection.handle_event(&event);
+    }
+}
+
+fn main() {
+    let event_loop = EventLoopBuilder::<()>::default().build().unwrap();
+
+    let (window, display) = animation::create_display(&event_loop, "solar").unwrap();
+
+    let mut app = MyWindow::new(Canvas::new(), window, display);
+    event_loop.run_app(&mut app).unwrap();
+}
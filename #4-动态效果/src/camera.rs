@@ -0,0 +1,318 @@
+//! Cameras producing view and projection matrices from input.
+//!
+//! Movement reads the [`MOVE_FORWARD`], [`MOVE_RIGHT`] and [`ASCEND`] axes of an
+//! [`ActionMap`] and scales with the step. Mouse look uses raw device motion, applied once per
+//! frame rather than per step, and only while [`CameraInput::mouse_look`] is set, which scenes
+//! tie to their [`CursorGrab`](crate::CursorGrab).
+
+use std::time::Duration;
+
+use glium::winit::event::MouseButton;
+use mats::radian;
+
+use crate::{ActionMap, InputState, Projection};
+
+pub const MOVE_FORWARD: &str = "MoveForward";
+pub const MOVE_RIGHT: &str = "MoveRight";
+pub const ASCEND: &str = "Ascend";
+
+pub struct CameraInput<'a> {
+    pub actions: &'a ActionMap,
+    pub input: &'a InputState,
+    pub mouse_look: bool,
+}
+
+pub trait Camera {
+    /// Advances the camera by one fixed step of `Drawable::update`.
+    fn update(&mut self, dt: Duration, input: &CameraInput);
+
+    /// View matrix interpolated between the last two updates by `alpha`.
+    fn view(&self, alpha: f32) -> mats::Mat4<f32>;
+
+    fn projection(&self) -> &Projection;
+
+    /// Forward `WindowEvent::Resized` to this so the aspect ratio stays current.
+    fn projection_mut(&mut self) -> &mut Projection;
+}
+
+/// Unit vector the camera looks along for the given angles in degrees.
+fn direction(yaw: f32, pitch: f32) -> mats::Vec3<f32> {
+    let direction = mats::Vec4::from([0.0, 0.0, -1.0, 0.0]);
+    let direction = direction * mats::rotate3_x(radian(pitch));
+    let direction = direction * mats::rotate3_y(radian(yaw));
+    direction.xyz()
+}
+
+fn lerp(from: mats::Vec3<f32>, to: mats::Vec3<f32>, alpha: f32) -> mats::Vec3<f32> {
+    from + (to - from) * alpha
+}
+
+fn up() -> mats::Vec3<f32> {
+    [0.0, 1.0, 0.0].into()
+}
+
+/// Turns by the raw motion gathered over the frame. Only the first update of a frame sees it,
+/// so the rotation does not depend on how many updates the frame runs.
+fn look(yaw: &mut f32, pitch: &mut f32, input: &CameraInput) {
+    if !input.mouse_look {
+        return;
+    }
+    let (dyaw, dpitch) = input.actions.look().apply(input.input.raw_mouse_delta());
+    *yaw = (*yaw + dyaw).rem_euclid(360.0);
+    *pitch = (*pitch + dpitch).clamp(-89.9, 89.9);
+}
+
+/// Walks on the horizontal plane, `ASCEND` moves straight up and down.
+pub struct FpsCamera {
+    pub position: mats::Vec3<f32>,
+    previous: mats::Vec3<f32>,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Units per second.
+    pub speed: f32,
+    pub projection: Projection,
+}
+
+impl FpsCamera {
+    pub fn new(position: mats::Vec3<f32>) -> Self {
+        Self {
+            position,
+            previous: position,
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 2.0,
+            projection: Projection::default(),
+        }
+    }
+}
+
+impl Camera for FpsCamera {
+    fn update(&mut self, dt: Duration, input: &CameraInput) {
+        look(&mut self.yaw, &mut self.pitch, input);
+
+        self.previous = self.position;
+        let step = self.speed * dt.as_secs_f32();
+        let forward = direction(self.yaw, 0.0);
+        let right = direction(self.yaw + 90.0, 0.0);
+        let actions = input.actions;
+        self.position += forward * (step * actions.axis(MOVE_FORWARD, input.input));
+        self.position += right * (step * actions.axis(MOVE_RIGHT, input.input));
+        self.position += up() * (step * actions.axis(ASCEND, input.input));
+    }
+
+    fn view(&self, alpha: f32) -> mats::Mat4<f32> {
+        let position = lerp(self.previous, self.position, alpha);
+        let target = position + direction(self.yaw, self.pitch);
+        mats::look_at(position, target, up())
+    }
+
+    fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    fn projection_mut(&mut self) -> &mut Projection {
+        &mut self.projection
+    }
+}
+
+/// Flies along the view direction including pitch, `ASCEND` follows world up.
+pub struct FreeFlyCamera {
+    pub position: mats::Vec3<f32>,
+    previous: mats::Vec3<f32>,
+    pub yaw: f32,
+    pub pitch: f32,
+    pub speed: f32,
+    pub projection: Projection,
+}
+
+impl FreeFlyCamera {
+    pub fn new(position: mats::Vec3<f32>) -> Self {
+        Self {
+            position,
+            previous: position,
+            yaw: 0.0,
+            pitch: 0.0,
+            speed: 4.0,
+            projection: Projection::default(),
+        }
+    }
+}
+
+impl Camera for FreeFlyCamera {
+    fn update(&mut self, dt: Duration, input: &CameraInput) {
+        look(&mut self.yaw, &mut self.pitch, input);
+
+        self.previous = self.position;
+        let step = self.speed * dt.as_secs_f32();
+        let forward = direction(self.yaw, self.pitch);
+        let right = direction(self.yaw + 90.0, 0.0);
+        let actions = input.actions;
+        self.position += forward * (step * actions.axis(MOVE_FORWARD, input.input));
+        self.position += right * (step * actions.axis(MOVE_RIGHT, input.input));
+        self.position += up() * (step * actions.axis(ASCEND, input.input));
+    }
+
+    fn view(&self, alpha: f32) -> mats::Mat4<f32> {
+        let position = lerp(self.previous, self.position, alpha);
+        let target = position + direction(self.yaw, self.pitch);
+        mats::look_at(position, target, up())
+    }
+
+    fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    fn projection_mut(&mut self) -> &mut Projection {
+        &mut self.projection
+    }
+}
+
+/// Circles `target`, dragging with the left mouse button rotates and scrolling zooms.
+///
+/// Like mouse look, drag and scroll are consumed by the first update of a frame.
+pub struct OrbitCamera {
+    pub target: mats::Vec3<f32>,
+    pub distance: f32,
+    previous_distance: f32,
+    pub yaw: f32,
+    pub pitch: f32,
+    /// Degrees per pixel of cursor drag.
+    pub rotate_speed: f32,
+    /// Fraction of the distance covered per scroll line.
+    pub zoom_speed: f32,
+    pub min_distance: f32,
+    pub max_distance: f32,
+    pub projection: Projection,
+}
+
+impl OrbitCamera {
+    pub fn new(target: mats::Vec3<f32>, distance: f32) -> Self {
+        Self {
+            target,
+            distance,
+            previous_distance: distance,
+            yaw: 0.0,
+            pitch: -20.0,
+            rotate_speed: 0.3,
+            zoom_speed: 0.1,
+            min_distance: 0.5,
+            max_distance: 50.0,
+            projection: Projection::default(),
+        }
+    }
+}
+
+impl Camera for OrbitCamera {
+    fn update(&mut self, _dt: Duration, input: &CameraInput) {
+        if input.input.is_button_pressed(MouseButton::Left) {
+            let (dx, dy) = input.input.mouse_delta();
+            self.yaw = (self.yaw + dx as f32 * self.rotate_speed).rem_euclid(360.0);
+            self.pitch = (self.pitch + dy as f32 * self.rotate_speed).clamp(-89.9, 89.9);
+        }
+
+        self.previous_distance = self.distance;
+        let (_, scroll) = input.input.scroll();
+        self.distance = (self.distance * (1.0 - self.zoom_speed).powf(scroll))
+            .clamp(self.min_distance, self.max_distance);
+    }
+
+    fn view(&self, alpha: f32) -> mats::Mat4<f32> {
+        let distance = self.previous_distance + (self.distance - self.previous_distance) * alpha;
+        let eye = self.target - direction(self.yaw, self.pitch) * distance;
+        mats::look_at(eye, self.target, up())
+    }
+
+    fn projection(&self) -> &Projection {
+        &self.projection
+    }
+
+    fn projection_mut(&mut self) -> &mut Projection {
+        &mut self.projection
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use common::run_updates;
+    use glium::Surface;
+
+    use super::*;
+    use crate::{Drawable, FrameContext, Timestep};
+
+    struct Scene<C> {
+        camera: C,
+        actions: ActionMap,
+    }
+
+    impl<C: Camera> Scene<C> {
+        fn new(camera: C) -> Self {
+            Self {
+                camera,
+                actions: ActionMap::new(),
+            }
+        }
+
+        /// Runs a frame with `steps` updates.
+        fn frame(&mut self, input: &mut InputState, steps: u32) {
+            let mut timestep = Timestep::new(Duration::from_millis(10));
+            let delta = timestep.step() * steps;
+            run_updates(self, &mut timestep, delta, input);
+        }
+    }
+
+    impl<C: Camera> Drawable for Scene<C> {
+        fn update(&mut self, dt: Duration, input: &InputState) {
+            let input = CameraInput {
+                actions: &self.actions,
+                input,
+                mouse_look: true,
+            };
+            self.camera.update(dt, &input);
+        }
+
+        fn draw<S: Surface>(&mut self, _target: &mut S, _context: &FrameContext) {}
+    }
+
+    #[test]
+    fn look_turns_once_per_frame() {
+        for steps in [1, 4] {
+            let mut scene = Scene::new(FpsCamera::new([0.0, 0.0, 0.0].into()));
+            let mut input = InputState::new();
+            input.add_raw_motion(200.0, -100.0);
+            input.add_raw_motion(100.0, 0.0);
+
+            scene.frame(&mut input, steps);
+            assert_eq!((scene.camera.yaw, scene.camera.pitch), (15.0, -5.0));
+        }
+    }
+
+    #[test]
+    fn orbit_drag_and_zoom_apply_once_per_frame() {
+        for steps in [1, 3] {
+            let mut scene = Scene::new(OrbitCamera::new([0.0, 0.0, 0.0].into(), 10.0));
+            scene.camera.pitch = 0.0;
+            let mut input = InputState::new();
+            input.move_cursor(100.0, 100.0);
+            input.press_button(MouseButton::Left);
+            input.move_cursor(110.0, 95.0);
+            input.scroll_by(0.0, 2.0);
+
+            scene.frame(&mut input, steps);
+            assert!((scene.camera.yaw - 3.0).abs() < 1e-5);
+            assert!((scene.camera.pitch + 1.5).abs() < 1e-5);
+            assert!((scene.camera.distance - 8.1).abs() < 1e-5);
+        }
+    }
+
+    #[test]
+    fn free_fly_look_waits_for_an_update() {
+        let mut scene = Scene::new(FreeFlyCamera::new([0.0, 0.0, 0.0].into()));
+        let mut input = InputState::new();
+        input.add_raw_motion(-20.0, 40.0);
+
+        scene.frame(&mut input, 0);
+        assert_eq!((scene.camera.yaw, scene.camera.pitch), (0.0, 0.0));
+        scene.frame(&mut input, 3);
+        assert_eq!((scene.camera.yaw, scene.camera.pitch), (359.0, 2.0));
+    }
+}
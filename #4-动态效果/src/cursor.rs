@@ -0,0 +1,67 @@
+use glium::winit::window::{CursorGrabMode, Window};
+use serde::Deserialize;
+
+/// Tracks whether the cursor is captured for mouse look.
+#[derive(Default)]
+pub struct CursorGrab {
+    grabbed: bool,
+}
+
+impl CursorGrab {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn is_grabbed(&self) -> bool {
+        self.grabbed
+    }
+
+    /// Locks and hides the cursor. Platforms without `Locked` support (Windows, X11) confine it
+    /// to the window instead, raw `DeviceEvent::MouseMotion` keeps flowing either way.
+    pub fn grab(&mut self, window: &Window) {
+        let result = window
+            .set_cursor_grab(CursorGrabMode::Locked)
+            .or_else(|_| window.set_cursor_grab(CursorGrabMode::Confined));
+        match result {
+            Ok(()) => {
+                window.set_cursor_visible(false);
+                self.grabbed = true;
+            }
+            Err(err) => eprintln!("failed to grab the cursor: {err}"),
+        }
+    }
+
+    pub fn release(&mut self, window: &Window) {
+        if let Err(err) = window.set_cursor_grab(CursorGrabMode::None) {
+            eprintln!("failed to release the cursor: {err}");
+        }
+        window.set_cursor_visible(true);
+        self.grabbed = false;
+    }
+}
+
+/// Turns raw mouse motion into yaw and pitch, read from the `[look]` table of a bindings file.
+#[derive(Debug, Clone, Copy, Deserialize)]
+#[serde(default)]
+pub struct LookSettings {
+    /// Degrees per raw motion unit.
+    pub sensitivity: f32,
+    pub invert_y: bool,
+}
+
+impl Default for LookSettings {
+    fn default() -> Self {
+        Self {
+            sensitivity: 0.05,
+            invert_y: false,
+        }
+    }
+}
+
+impl LookSettings {
+    /// `(yaw, pitch)` in degrees for a raw mouse delta.
+    pub fn apply(&self, (dx, dy): (f64, f64)) -> (f32, f32) {
+        let dy = if self.invert_y { -dy } else { dy };
+        (dx as f32 * self.sensitivity, dy as f32 * self.sensitivity)
+    }
+}
@@ -0,0 +1,386 @@
+//! glTF 2.0 scenes (`.gltf` with embedded or external buffers, and `.glb`).
+//!
+//! Nodes keep their glTF indices, so `GltfNode::children`, `GltfNode::mesh` and the material
+//! and image references can be used to index the vectors of [`GltfScene`] directly.
+
+use std::{fmt, path::Path};
+
+use gltf::{image::Format, mesh::Mode};
+use image::RgbaImage;
+
+use crate::{Mesh, Transform, scene::SceneGraph};
+
+#[derive(Debug)]
+pub enum GltfError {
+    Import(gltf::Error),
+    MissingPositions { mesh: usize, primitive: usize },
+}
+
+impl fmt::Display for GltfError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Import(err) => write!(f, "failed to import glTF: {err}"),
+            Self::MissingPositions { mesh, primitive } => {
+                write!(f, "primitive {primitive} of mesh {mesh} has no positions")
+            }
+        }
+    }
+}
+
+impl std::error::Error for GltfError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Import(err) => Some(err),
+            _ => None,
+        }
+    }
+}
+
+impl From<gltf::Error> for GltfError {
+    fn from(err: gltf::Error) -> Self {
+        Self::Import(err)
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfNode {
+    pub name: Option<String>,
+    pub translation: [f32; 3],
+    /// Unit quaternion `[x, y, z, w]`.
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+    pub mesh: Option<usize>,
+    pub children: Vec<usize>,
+}
+
+impl GltfNode {
+    pub fn transform(&self) -> Transform {
+        Transform {
+            translation: self.translation,
+            rotation: self.rotation,
+            scale: self.scale,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfPrimitive {
+    pub mesh: Mesh,
+    pub material: Option<usize>,
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfMesh {
+    pub name: Option<String>,
+    pub primitives: Vec<GltfPrimitive>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AlphaMode {
+    Opaque,
+    /// Fragments with alpha below the cutoff are discarded.
+    Mask(f32),
+    Blend,
+}
+
+/// Metallic-roughness material. Texture fields index [`GltfScene::images`].
+#[derive(Debug, Clone)]
+pub struct GltfMaterial {
+    pub name: Option<String>,
+    pub base_color: [f32; 4],
+    pub base_color_texture: Option<usize>,
+    pub metallic: f32,
+    pub roughness: f32,
+    /// Roughness in the green channel, metalness in blue.
+    pub metallic_roughness_texture: Option<usize>,
+    pub normal_texture: Option<usize>,
+    pub emissive: [f32; 3],
+    pub emissive_texture: Option<usize>,
+    pub alpha_mode: AlphaMode,
+    pub double_sided: bool,
+}
+
+#[derive(Debug, Clone)]
+pub struct GltfScene {
+    pub nodes: Vec<GltfNode>,
+    /// Root nodes of the default scene, or of the first scene if none is marked default.
+    pub roots: Vec<usize>,
+    pub meshes: Vec<GltfMesh>,
+    pub materials: Vec<GltfMaterial>,
+    /// Every image converted to 8-bit RGBA, 16-bit and float channels included.
+    pub images: Vec<RgbaImage>,
+}
+
+impl GltfScene {
+    /// Imports a file, resolving external buffers and images relative to it.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, GltfError> {
+        let (document, buffers, images) = gltf::import(path)?;
+        Self::build(&document, &buffers, &images)
+    }
+
+    /// Imports a `.glb` or a `.gltf` whose buffers and images are all embedded.
+    pub fn from_slice(data: &[u8]) -> Result<Self, GltfError> {
+        let gltf::Gltf { document, blob } = gltf::Gltf::from_slice(data)?;
+        let buffers = gltf::import_buffers(&document, None, blob)?;
+        // `gltf::import_slice` rejects every image URI, embedded `data:` ones too, so they are
+        // decoded here with a base path that is never used.
+        let images = document
+            .images()
+            .map(|image| match image.source() {
+                gltf::image::Source::Uri { uri, .. } if !uri.starts_with("data:") => {
+                    Err(gltf::Error::ExternalReferenceInSliceImport)
+                }
+                source => gltf::image::Data::from_source(source, Some(Path::new("")), &buffers),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+        Self::build(&document, &buffers, &images)
+    }
+
+    fn build(
+        document: &gltf::Document,
+        buffers: &[gltf::buffer::Data],
+        images: &[gltf::image::Data],
+    ) -> Result<Self, GltfError> {
+        let nodes = document
+            .nodes()
+            .map(|node| {
+                let (translation, rotation, scale) = node.transform().decomposed();
+                GltfNode {
+                    name: node.name().map(str::to_owned),
+                    translation,
+                    rotation,
+                    scale,
+                    mesh: node.mesh().map(|mesh| mesh.index()),
+                    children: node.children().map(|child| child.index()).collect(),
+                }
+            })
+            .collect();
+
+        let roots = document
+            .default_scene()
+            .or_else(|| document.scenes().next())
+            .map(|scene| scene.nodes().map(|node| node.index()).collect())
+            .unwrap_or_default();
+
+        let meshes = document
+            .meshes()
+            .map(|mesh| {
+                let primitives = mesh
+                    .primitives()
+                    .filter(|primitive| {
+                        matches!(
+                            primitive.mode(),
+                            Mode::Triangles | Mode::TriangleStrip | Mode::TriangleFan
+                        )
+                    })
+                    .map(|primitive| read_primitive(&mesh, &primitive, buffers))
+                    .collect::<Result<_, _>>()?;
+                Ok(GltfMesh {
+                    name: mesh.name().map(str::to_owned),
+                    primitives,
+                })
+            })
+            .collect::<Result<_, GltfError>>()?;
+
+        let materials = document.materials().map(read_material).collect();
+
+        let images = images.iter().map(to_rgba).collect();
+
+        Ok(Self {
+            nodes,
+            roots,
+            meshes,
+            materials,
+            images,
+        })
+    }
+
+    /// The default scene as a [`SceneGraph`] whose items index [`GltfScene::meshes`].
+    pub fn scene_graph(&self) -> SceneGraph<usize> {
+        let mut graph = SceneGraph::new();
+        let mut stack = self
+            .roots
+            .iter()
+            .map(|&root| (root, None))
+            .collect::<Vec<_>>();
+        while let Some((index, parent)) = stack.pop() {
+            let node = &self.nodes[index];
+            let id = graph.add(parent, node.transform(), node.mesh);
+            stack.extend(node.children.iter().map(|&child| (child, Some(id))));
+        }
+        graph
+    }
+
+    /// Every primitive reachable from the roots together with its world matrix.
+    pub fn instances(&self) -> Vec<(mats::Mat4<f32>, &GltfPrimitive)> {
+        let mut instances = Vec::new();
+        self.scene_graph().traverse(|_, world, &mesh| {
+            for primitive in &self.meshes[mesh].primitives {
+                instances.push((world, primitive));
+            }
+        });
+        instances
+    }
+}
+
+fn read_primitive(
+    mesh: &gltf::Mesh,
+    primitive: &gltf::Primitive,
+    buffers: &[gltf::buffer::Data],
+) -> Result<GltfPrimitive, GltfError> {
+    let reader = primitive.reader(|buffer| buffers.get(buffer.index()).map(|data| &data.0[..]));
+    let positions = reader
+        .read_positions()
+        .ok_or(GltfError::MissingPositions {
+            mesh: mesh.index(),
+            primitive: primitive.index(),
+        })?
+        .collect::<Vec<_>>();
+
+    let count = positions.len();
+    let normals: Vec<[f32; 3]> = reader
+        .read_normals()
+        .map(|normals| normals.collect())
+        .unwrap_or_default();
+    // glTF puts the texture origin at the top left. Flipping v mirrors the texture space, so
+    // the bitangent sign in the tangents' w flips with it.
+    let tangents: Vec<[f32; 4]> = reader
+        .read_tangents()
+        .map(|tangents| tangents.map(|[x, y, z, w]| [x, y, z, -w]).collect())
+        .unwrap_or_default();
+    let tex_coords = reader
+        .read_tex_coords(0)
+        .map(|uvs| uvs.into_f32().map(|[u, v]| [u, 1.0 - v]).collect())
+        .unwrap_or_default();
+    let colors = reader
+        .read_colors(0)
+        .map(|colors| colors.into_rgba_f32().collect())
+        .unwrap_or_default();
+    let indices = reader
+        .read_indices()
+        .map(|indices| indices.into_u32().collect::<Vec<_>>())
+        .unwrap_or_else(|| (0..count as u32).collect());
+    let indices = match primitive.mode() {
+        Mode::TriangleStrip => (2..indices.len())
+            .flat_map(|i| {
+                if i % 2 == 0 {
+                    [indices[i - 2], indices[i - 1], indices[i]]
+                } else {
+                    [indices[i - 1], indices[i - 2], indices[i]]
+                }
+            })
+            .collect(),
+        Mode::TriangleFan => (2..indices.len())
+            .flat_map(|i| [indices[0], indices[i - 1], indices[i]])
+            .collect(),
+        _ => indices,
+    };
+
+    let has_normals = normals.len() == count;
+    let has_tangents = tangents.len() == count;
+    let source = Mesh {
+        positions,
+        normals,
+        tangents,
+        tex_coords,
+        colors,
+        indices: Vec::new(),
+    };
+    // Expand to the full vertex layout, filling in whatever the file left out.
+    let mut mesh = Mesh::new();
+    for vertex in source.vertices() {
+        mesh.push_vertex(vertex);
+    }
+    mesh.indices = indices;
+    if !has_normals {
+        mesh.compute_normals();
+    }
+    if !has_tangents {
+        mesh.compute_tangents();
+    }
+
+    Ok(GltfPrimitive {
+        mesh,
+        material: primitive.material().index(),
+    })
+}
+
+fn read_material(material: gltf::Material) -> GltfMaterial {
+    let image = |texture: gltf::Texture| texture.source().index();
+    let pbr = material.pbr_metallic_roughness();
+    GltfMaterial {
+        name: material.name().map(str::to_owned),
+        base_color: pbr.base_color_factor(),
+        base_color_texture: pbr.base_color_texture().map(|info| image(info.texture())),
+        metallic: pbr.metallic_factor(),
+        roughness: pbr.roughness_factor(),
+        metallic_roughness_texture: pbr
+            .metallic_roughness_texture()
+            .map(|info| image(info.texture())),
+        normal_texture: material
+            .normal_texture()
+            .map(|normal| image(normal.texture())),
+        emissive: material.emissive_factor(),
+        emissive_texture: material
+            .emissive_texture()
+            .map(|info| image(info.texture())),
+        alpha_mode: match material.alpha_mode() {
+            gltf::material::AlphaMode::Opaque => AlphaMode::Opaque,
+            gltf::material::AlphaMode::Mask => {
+                AlphaMode::Mask(material.alpha_cutoff().unwrap_or(0.5))
+            }
+            gltf::material::AlphaMode::Blend => AlphaMode::Blend,
+        },
+        double_sided: material.double_sided(),
+    }
+}
+
+/// Converts to 8-bit RGBA. Grey and two-channel images fill in the missing channels, wider
+/// channels are rounded to 8 bits and floats are clamped to `[0, 1]`.
+fn to_rgba(image: &gltf::image::Data) -> RgbaImage {
+    let (channels, values): (usize, Vec<u8>) = match image.format {
+        Format::R8 => (1, image.pixels.clone()),
+        Format::R8G8 => (2, image.pixels.clone()),
+        Format::R8G8B8 => (3, image.pixels.clone()),
+        Format::R8G8B8A8 => (4, image.pixels.clone()),
+        Format::R16 => (1, narrow_u16(&image.pixels)),
+        Format::R16G16 => (2, narrow_u16(&image.pixels)),
+        Format::R16G16B16 => (3, narrow_u16(&image.pixels)),
+        Format::R16G16B16A16 => (4, narrow_u16(&image.pixels)),
+        Format::R32G32B32FLOAT => (3, narrow_f32(&image.pixels)),
+        Format::R32G32B32A32FLOAT => (4, narrow_f32(&image.pixels)),
+    };
+    let pixels = values
+        .chunks_exact(channels)
+        .flat_map(|p| match *p {
+            [l] => [l, l, l, 255],
+            [r, g] => [r, g, 0, 255],
+            [r, g, b] => [r, g, b, 255],
+            [r, g, b, a] => [r, g, b, a],
+            _ => unreachable!("images have one to four channels"),
+        })
+        .collect();
+    RgbaImage::from_raw(image.width, image.height, pixels)
+        .expect("glTF image data matches its dimensions")
+}
+
+/// 16-bit channels in native byte order, as the gltf crate decodes them.
+fn narrow_u16(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .chunks_exact(2)
+        .map(|b| {
+            let value = u16::from_ne_bytes([b[0], b[1]]) as u32;
+            ((value * 255 + 32767) / 65535) as u8
+        })
+        .collect()
+}
+
+fn narrow_f32(bytes: &[u8]) -> Vec<u8> {
+    bytes
+        .chunks_exact(4)
+        .map(|b| {
+            let value = f32::from_ne_bytes([b[0], b[1], b[2], b[3]]);
+            (value.clamp(0.0, 1.0) * 255.0).round() as u8
+        })
+        .collect()
+}
@@ -1,3 +1,17 @@
-mod window;
+mod actions;
+pub mod camera;
+mod cursor;
+pub mod gltf_scene;
+pub mod obj;
+pub mod scene;
 
-pub use window::{Drawable, MyWindow};
\ No newline at end of file
+pub use actions::{ActionMap, ActionMapError, AnalogBinding, AnalogSource, Axis, Binding};
+pub use common::{
+    Capture, Clock, ColorSpace, DepthConvention, Drawable, FixedClock, FrameContext, InputState,
+    Material, MaterialUniforms, MaterialValue, Mesh, MeshVertex, MyWindow, Offscreen,
+    PreprocessError, Preprocessed, Preprocessor, Projection, ProjectionKind, RealClock,
+    RenderState, RenderStateError, Resources, SamplerDescriptor, ScaledClock, ShaderError,
+    ShaderFile, Texture, TextureError, TextureLoader, TextureSampler, Timestep, Transform,
+    create_display, ecs, lighting, mip_levels, missing_image, primitives, shader,
+};
+pub use cursor::{CursorGrab, LookSettings};
\ No newline at end of file
@@ -0,0 +1,515 @@
+//! Wavefront OBJ models with their MTL materials.
+//!
+//! Supports positions (with optional vertex colors), texture coordinates, normals, polygonal
+//! faces (triangulated as fans), negative indices, `o`/`g` objects and groups, `usemtl` and
+//! `mtllib`. Other statements such as smoothing groups, lines and points are skipped.
+
+use std::{
+    collections::HashMap,
+    fmt, fs, io,
+    path::{Path, PathBuf},
+};
+
+use common::vector::{cross, length, normalize, sub};
+
+use crate::{Mesh, MeshVertex};
+
+#[derive(Debug)]
+pub enum ObjError {
+    Io {
+        path: PathBuf,
+        source: io::Error,
+    },
+    Parse {
+        file: String,
+        line: usize,
+        message: String,
+    },
+}
+
+impl fmt::Display for ObjError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Io { path, source } => write!(f, "failed to read {}: {source}", path.display()),
+            Self::Parse {
+                file,
+                line,
+                message,
+            } => write!(f, "{file}:{line}: {message}"),
+        }
+    }
+}
+
+impl std::error::Error for ObjError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::Io { source, .. } => Some(source),
+            Self::Parse { .. } => None,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct ObjMaterial {
+    pub name: String,
+    pub ambient: [f32; 3],
+    pub diffuse: [f32; 3],
+    pub specular: [f32; 3],
+    pub shininess: f32,
+    /// Opacity from `d`, or `1 - Tr`.
+    pub dissolve: f32,
+    /// Texture paths, resolved relative to the MTL file.
+    pub diffuse_map: Option<PathBuf>,
+    pub specular_map: Option<PathBuf>,
+    pub normal_map: Option<PathBuf>,
+}
+
+impl ObjMaterial {
+    fn new(name: &str) -> Self {
+        Self {
+            name: name.to_owned(),
+            ambient: [0.0; 3],
+            diffuse: [0.8; 3],
+            specular: [0.0; 3],
+            shininess: 0.0,
+            dissolve: 1.0,
+            diffuse_map: None,
+            specular_map: None,
+            normal_map: None,
+        }
+    }
+}
+
+/// Faces sharing an object, group and material.
+#[derive(Debug, Clone)]
+pub struct ObjMesh {
+    pub object: String,
+    pub group: String,
+    pub material: Option<String>,
+    pub mesh: Mesh,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct ObjModel {
+    pub meshes: Vec<ObjMesh>,
+    pub materials: HashMap<String, ObjMaterial>,
+}
+
+impl ObjModel {
+    /// Reads the OBJ file and every MTL library it references.
+    pub fn load(path: impl AsRef<Path>) -> Result<Self, ObjError> {
+        let path = path.as_ref();
+        let source = read(path)?;
+        let (mut model, libraries) = parse_obj(&source, &path.display().to_string())?;
+
+        let dir = path.parent().unwrap_or(Path::new(""));
+        for library in libraries {
+            let path = dir.join(library);
+            let source = read(&path)?;
+            let materials = parse_mtl(
+                &source,
+                &path.display().to_string(),
+                path.parent().unwrap_or(Path::new("")),
+            )?;
+            model.materials.extend(materials);
+        }
+        Ok(model)
+    }
+
+    /// Parses OBJ source without loading material libraries, `file` only names it in errors.
+    pub fn parse(source: &str, file: &str) -> Result<Self, ObjError> {
+        parse_obj(source, file).map(|(model, _)| model)
+    }
+
+    pub fn material(&self, mesh: &ObjMesh) -> Option<&ObjMaterial> {
+        self.materials.get(mesh.material.as_deref()?)
+    }
+
+    /// All meshes combined into one, for drawing the model with a single material.
+    pub fn merged(&self) -> Mesh {
+        let mut merged = Mesh::new();
+        for mesh in &self.meshes {
+            merged.append(&mesh.mesh);
+        }
+        merged
+    }
+}
+
+fn read(path: &Path) -> Result<String, ObjError> {
+    fs::read_to_string(path).map_err(|source| ObjError::Io {
+        path: path.to_owned(),
+        source,
+    })
+}
+
+/// Tracks the line being parsed to build errors.
+struct Cursor<'a> {
+    file: &'a str,
+    line: usize,
+}
+
+impl Cursor<'_> {
+    fn error(&self, message: impl Into<String>) -> ObjError {
+        ObjError::Parse {
+            file: self.file.to_owned(),
+            line: self.line,
+            message: message.into(),
+        }
+    }
+
+    fn floats<const N: usize>(
+        &self,
+        keyword: &str,
+        args: &[&str],
+        required: usize,
+    ) -> Result<[f32; N], ObjError> {
+        if args.len() < required {
+            return Err(self.error(format!(
+                "`{keyword}` needs at least {required} values, found {}",
+                args.len()
+            )));
+        }
+        let mut values = [0.0; N];
+        for (value, arg) in values.iter_mut().zip(args) {
+            *value = arg
+                .parse()
+                .map_err(|_| self.error(format!("invalid number `{arg}` in `{keyword}`")))?;
+        }
+        Ok(values)
+    }
+
+    /// Resolves a 1-based or negative relative index into `0..len`.
+    fn index(&self, value: &str, len: usize, what: &str) -> Result<usize, ObjError> {
+        let index: i64 = value
+            .parse()
+            .map_err(|_| self.error(format!("invalid {what} index `{value}`")))?;
+        let resolved = match index {
+            0 => None,
+            i if i > 0 => Some(i as usize - 1),
+            i => len.checked_sub(i.unsigned_abs() as usize),
+        };
+        match resolved {
+            Some(i) if i < len => Ok(i),
+            _ => Err(self.error(format!(
+                "{what} index {index} is out of range ({len} defined)"
+            ))),
+        }
+    }
+}
+
+struct Builder {
+    object: String,
+    group: String,
+    material: Option<String>,
+    vertices: Vec<MeshVertex>,
+}
+
+impl Builder {
+    fn flush(&mut self, meshes: &mut Vec<ObjMesh>) {
+        if self.vertices.is_empty() {
+            return;
+        }
+        let mut mesh = Mesh::from_triangles(self.vertices.drain(..));
+        mesh.compute_tangents();
+        meshes.push(ObjMesh {
+            object: self.object.clone(),
+            group: self.group.clone(),
+            material: self.material.clone(),
+            mesh,
+        });
+    }
+}
+
+fn parse_obj(source: &str, file: &str) -> Result<(ObjModel, Vec<String>), ObjError> {
+    let mut positions = Vec::new();
+    let mut colors = Vec::new();
+    let mut tex_coords = Vec::new();
+    let mut normals = Vec::new();
+    let mut libraries = Vec::new();
+    let mut model = ObjModel::default();
+    let mut builder = Builder {
+        object: String::new(),
+        group: String::new(),
+        material: None,
+        vertices: Vec::new(),
+    };
+
+    let mut cursor = Cursor { file, line: 0 };
+    for (number, line) in source.lines().enumerate() {
+        cursor.line = number + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args = words.collect::<Vec<_>>();
+
+        match keyword {
+            "v" => {
+                let [x, y, z, r, g, b] = cursor.floats::<6>("v", &args, 3)?;
+                positions.push([x, y, z]);
+                // Six values are `x y z r g b`, four are `x y z w`.
+                colors.push(if args.len() >= 6 {
+                    [r, g, b, 1.0]
+                } else {
+                    [1.0; 4]
+                });
+            }
+            "vt" => {
+                let [u, v] = cursor.floats::<2>("vt", &args, 1)?;
+                tex_coords.push([u, v]);
+            }
+            "vn" => normals.push(cursor.floats::<3>("vn", &args, 3)?),
+            "f" => {
+                if args.len() < 3 {
+                    return Err(cursor.error(format!(
+                        "face needs at least 3 vertices, found {}",
+                        args.len()
+                    )));
+                }
+                let mut corners = Vec::with_capacity(args.len());
+                for arg in &args {
+                    let mut parts = arg.split('/');
+                    let position =
+                        cursor.index(parts.next().unwrap_or(""), positions.len(), "position")?;
+                    let tex_coord = match parts.next() {
+                        Some("") | None => None,
+                        Some(value) => {
+                            Some(cursor.index(value, tex_coords.len(), "texture coordinate")?)
+                        }
+                    };
+                    let normal = match parts.next() {
+                        Some("") | None => None,
+                        Some(value) => Some(cursor.index(value, normals.len(), "normal")?),
+                    };
+                    let mut vertex =
+                        MeshVertex::new(positions[position]).with_color(colors[position]);
+                    if let Some(i) = tex_coord {
+                        vertex.tex_coord = tex_coords[i];
+                    }
+                    if let Some(i) = normal {
+                        vertex.normal = normals[i];
+                    }
+                    corners.push((vertex, normal.is_some()));
+                }
+
+                // Faces without normals get the flat normal of the polygon.
+                let face_normal = cross(
+                    sub(corners[1].0.position, corners[0].0.position),
+                    sub(corners[2].0.position, corners[0].0.position),
+                );
+                for (vertex, has_normal) in &mut corners {
+                    if !*has_normal && length(face_normal) > 0.0 {
+                        vertex.normal = normalize(face_normal);
+                    }
+                }
+                for i in 1..corners.len() - 1 {
+                    builder
+                        .vertices
+                        .extend([corners[0].0, corners[i].0, corners[i + 1].0]);
+                }
+            }
+            "o" => {
+                builder.flush(&mut model.meshes);
+                builder.object = args.join(" ");
+                builder.group.clear();
+            }
+            "g" => {
+                builder.flush(&mut model.meshes);
+                builder.group = args.first().copied().unwrap_or_default().to_owned();
+            }
+            "usemtl" => {
+                builder.flush(&mut model.meshes);
+                builder.material = Some(args.join(" "));
+            }
+            "mtllib" => libraries.extend(args.iter().map(|s| s.to_string())),
+            _ => {}
+        }
+    }
+    builder.flush(&mut model.meshes);
+
+    Ok((model, libraries))
+}
+
+/// Parses an MTL library, texture paths are joined onto `dir`.
+pub fn parse_mtl(
+    source: &str,
+    file: &str,
+    dir: &Path,
+) -> Result<HashMap<String, ObjMaterial>, ObjError> {
+    let mut materials = HashMap::new();
+    let mut current: Option<ObjMaterial> = None;
+
+    let mut cursor = Cursor { file, line: 0 };
+    for (number, line) in source.lines().enumerate() {
+        cursor.line = number + 1;
+        let line = line.split('#').next().unwrap_or("").trim();
+        let mut words = line.split_whitespace();
+        let Some(keyword) = words.next() else {
+            continue;
+        };
+        let args = words.collect::<Vec<_>>();
+
+        if keyword == "newmtl" {
+            if let Some(material) = current.take() {
+                materials.insert(material.name.clone(), material);
+            }
+            current = Some(ObjMaterial::new(&args.join(" ")));
+            continue;
+        }
+        let Some(material) = current.as_mut() else {
+            return Err(cursor.error(format!("`{keyword}` before any `newmtl`")));
+        };
+        // Map statements may carry options like `-bm 1.0`, the path is the last word.
+        let map = || {
+            args.last()
+                .map(|path| dir.join(path))
+                .ok_or_else(|| cursor.error(format!("`{keyword}` is missing a texture path")))
+        };
+        match keyword {
+            "Ka" => material.ambient = cursor.floats::<3>(keyword, &args, 3)?,
+            "Kd" => material.diffuse = cursor.floats::<3>(keyword, &args, 3)?,
+            "Ks" => material.specular = cursor.floats::<3>(keyword, &args, 3)?,
+            "Ns" => material.shininess = cursor.floats::<1>(keyword, &args, 1)?[0],
+            "d" => material.dissolve = cursor.floats::<1>(keyword, &args, 1)?[0],
+            "Tr" => material.dissolve = 1.0 - cursor.floats::<1>(keyword, &args, 1)?[0],
+            "map_Kd" => material.diffuse_map = Some(map()?),
+            "map_Ks" => material.specular_map = Some(map()?),
+            "map_Bump" | "map_bump" | "bump" | "norm" => material.normal_map = Some(map()?),
+            _ => {}
+        }
+    }
+    if let Some(material) = current {
+        materials.insert(material.name.clone(), material);
+    }
+
+    Ok(materials)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn parse_error_line(source: &str) -> usize {
+        match ObjModel::parse(source, "test.obj") {
+            Err(ObjError::Parse { file, line, .. }) => {
+                assert_eq!(file, "test.obj");
+                line
+            }
+            other => panic!("expected a parse error, got {other:?}"),
+        }
+    }
+
+    #[test]
+    fn parse_errors_name_the_line() {
+        assert_eq!(parse_error_line("v 0 0 0\n\nv 1 x 0\n"), 3);
+        assert_eq!(parse_error_line("# comment\nv 0 0\n"), 2);
+        assert_eq!(parse_error_line("v 0 0 0\nv 1 0 0\nf 1 2\n"), 3);
+        assert_eq!(parse_error_line("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1 2 4\n"), 4);
+        assert_eq!(parse_error_line("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 0 1 2\n"), 4);
+        assert_eq!(
+            parse_error_line("v 0 0 0\nv 1 0 0\nv 0 1 0\nf 1/1 2 3\n"),
+            4
+        );
+        let err = ObjModel::parse("v 0 0 0\nf 1 1 -2\n", "test.obj").unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "test.obj:2: position index -2 is out of range (1 defined)"
+        );
+    }
+
+    #[test]
+    fn polygons_are_triangulated_as_fans() {
+        let model = ObjModel::parse(
+            "v 0 0 0\nv 1 0 0\nv 1 1 0\nv 0.5 2 0\nv 0 1 0\nf 1 2 3 4 5\n",
+            "pentagon.obj",
+        )
+        .unwrap();
+        let mesh = &model.meshes[0].mesh;
+        assert_eq!(mesh.vertex_count(), 5);
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3, 0, 3, 4]);
+        // No `vn`, so every corner gets the flat face normal.
+        assert_eq!(mesh.normals, [[0.0, 0.0, 1.0]; 5]);
+    }
+
+    #[test]
+    fn negative_indices_count_back_from_the_last_element() {
+        let model = ObjModel::parse(
+            "v 0 0 0\nv 1 0 0\nv 0 1 0\nvt 0 0\nvt 1 0\nvt 0 1\nvn 0 0 -1\n\
+             f -3/-3/-1 -2/-2/-1 -1/-1/-1\n",
+            "negative.obj",
+        )
+        .unwrap();
+        let mesh = &model.meshes[0].mesh;
+        assert_eq!(
+            mesh.positions,
+            [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]]
+        );
+        assert_eq!(mesh.tex_coords, [[0.0, 0.0], [1.0, 0.0], [0.0, 1.0]]);
+        assert_eq!(mesh.normals, [[0.0, 0.0, -1.0]; 3]);
+    }
+
+    #[test]
+    fn objects_groups_and_materials_split_meshes() {
+        let model = ObjModel::parse(
+            "mtllib box.mtl\n\
+             v 0 0 0\nv 1 0 0\nv 0 1 0\n\
+             o box\ng top\nusemtl wood\nf 1 2 3\n\
+             usemtl metal\nf 1 2 3\n\
+             g side\nf 1 2 3\n\
+             o lid\nf 1 2 3\n",
+            "box.obj",
+        )
+        .unwrap();
+        let meshes: Vec<_> = model
+            .meshes
+            .iter()
+            .map(|mesh| {
+                (
+                    mesh.object.as_str(),
+                    mesh.group.as_str(),
+                    mesh.material.as_deref(),
+                    mesh.mesh.triangle_count(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            meshes,
+            [
+                ("box", "top", Some("wood"), 1),
+                ("box", "top", Some("metal"), 1),
+                ("box", "side", Some("metal"), 1),
+                ("lid", "", Some("metal"), 1),
+            ]
+        );
+        assert_eq!(model.merged().triangle_count(), 4);
+    }
+
+    #[test]
+    fn mtl_materials_resolve_maps_against_the_directory() {
+        let materials = parse_mtl(
+            "newmtl wood\nKd 0.5 0.25 0\nNs 32\nTr 0.25\nmap_Kd -bm 1.0 wood.png\n\
+             newmtl metal\nKs 1 1 1\nd 0.5\nbump metal_n.png\n",
+            "box.mtl",
+            Path::new("models"),
+        )
+        .unwrap();
+        let wood = &materials["wood"];
+        assert_eq!(wood.diffuse, [0.5, 0.25, 0.0]);
+        assert_eq!(wood.shininess, 32.0);
+        assert_eq!(wood.dissolve, 0.75);
+        assert_eq!(wood.diffuse_map, Some(PathBuf::from("models/wood.png")));
+        let metal = &materials["metal"];
+        assert_eq!(metal.specular, [1.0; 3]);
+        assert_eq!(metal.dissolve, 0.5);
+        assert_eq!(metal.normal_map, Some(PathBuf::from("models/metal_n.png")));
+    }
+
+    #[test]
+    fn mtl_errors_name_the_line() {
+        let err = parse_mtl("# wood\nKd 1 1 1\n", "box.mtl", Path::new("")).unwrap_err();
+        assert!(matches!(err, ObjError::Parse { line: 2, .. }));
+        let err = parse_mtl("newmtl wood\nKd 1 1\n", "box.mtl", Path::new("")).unwrap_err();
+        assert!(matches!(err, ObjError::Parse { line: 2, .. }));
+    }
+}
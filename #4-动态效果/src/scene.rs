@@ -0,0 +1,252 @@
+//! Hierarchy of nodes with local transforms and cached world matrices.
+//!
+//! Changing a node's transform or parent only marks it dirty; world matrices of it and its
+//! descendants are recomputed on the next [`SceneGraph::update`] or traversal.
+
+use crate::Transform;
+
+/// Handle to a node. Slots of removed nodes are reused, and the generation keeps an old
+/// handle from reaching the node that took its slot.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct NodeId {
+    index: u32,
+    generation: u32,
+}
+
+struct Node<T> {
+    transform: Transform,
+    parent: Option<NodeId>,
+    children: Vec<NodeId>,
+    world: mats::Mat4<f32>,
+    dirty: bool,
+    item: Option<T>,
+}
+
+struct Slot<T> {
+    generation: u32,
+    node: Option<Node<T>>,
+}
+
+/// Nodes carry an optional `T`, typically whatever a scene needs to issue a draw call for them.
+pub struct SceneGraph<T> {
+    nodes: Vec<Slot<T>>,
+    roots: Vec<NodeId>,
+    free: Vec<u32>,
+}
+
+impl<T> Default for SceneGraph<T> {
+    fn default() -> Self {
+        Self {
+            nodes: Vec::new(),
+            roots: Vec::new(),
+            free: Vec::new(),
+        }
+    }
+}
+
+impl<T> SceneGraph<T> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn add(&mut self, parent: Option<NodeId>, transform: Transform, item: Option<T>) -> NodeId {
+        let node = Node {
+            transform,
+            parent,
+            children: Vec::new(),
+            world: transform.matrix(),
+            dirty: true,
+            item,
+        };
+        let index = match self.free.pop() {
+            Some(index) => {
+                self.nodes[index as usize].node = Some(node);
+                index
+            }
+            None => {
+                self.nodes.push(Slot {
+                    generation: 0,
+                    node: Some(node),
+                });
+                self.nodes.len() as u32 - 1
+            }
+        };
+        let id = NodeId {
+            index,
+            generation: self.nodes[index as usize].generation,
+        };
+        match parent {
+            Some(parent) => self.node_mut(parent).children.push(id),
+            None => self.roots.push(id),
+        }
+        id
+    }
+
+    /// Removes the node together with all its descendants.
+    ///
+    /// # Panics
+    ///
+    /// If the node was already removed.
+    pub fn remove(&mut self, id: NodeId) {
+        self.detach(id);
+        let mut stack = vec![id];
+        while let Some(id) = stack.pop() {
+            let slot = &mut self.nodes[id.index as usize];
+            let node = slot.node.take().expect("node was removed");
+            slot.generation += 1;
+            stack.extend(node.children);
+            self.free.push(id.index);
+        }
+    }
+
+    /// Whether `id` refers to a node that has not been removed.
+    pub fn contains(&self, id: NodeId) -> bool {
+        self.nodes
+            .get(id.index as usize)
+            .is_some_and(|slot| slot.generation == id.generation && slot.node.is_some())
+    }
+
+    /// Moves the node under `parent`, or to the top level with `None`.
+    ///
+    /// # Panics
+    ///
+    /// If `parent` is the node itself or one of its descendants.
+    pub fn set_parent(&mut self, id: NodeId, parent: Option<NodeId>) {
+        let mut ancestor = parent;
+        while let Some(current) = ancestor {
+            assert!(current != id, "cannot parent a node to its own descendant");
+            ancestor = self.node(current).parent;
+        }
+
+        self.detach(id);
+        match parent {
+            Some(parent) => self.node_mut(parent).children.push(id),
+            None => self.roots.push(id),
+        }
+        let node = self.node_mut(id);
+        node.parent = parent;
+        node.dirty = true;
+    }
+
+    pub fn parent(&self, id: NodeId) -> Option<NodeId> {
+        self.node(id).parent
+    }
+
+    pub fn children(&self, id: NodeId) -> &[NodeId] {
+        &self.node(id).children
+    }
+
+    pub fn transform(&self, id: NodeId) -> &Transform {
+        &self.node(id).transform
+    }
+
+    /// Marks the node dirty, its world matrix is refreshed on the next update.
+    pub fn transform_mut(&mut self, id: NodeId) -> &mut Transform {
+        let node = self.node_mut(id);
+        node.dirty = true;
+        &mut node.transform
+    }
+
+    pub fn item(&self, id: NodeId) -> Option<&T> {
+        self.node(id).item.as_ref()
+    }
+
+    pub fn item_mut(&mut self, id: NodeId) -> Option<&mut T> {
+        self.node_mut(id).item.as_mut()
+    }
+
+    /// World matrix as of the last update.
+    pub fn world(&self, id: NodeId) -> mats::Mat4<f32> {
+        self.node(id).world
+    }
+
+    /// Recomputes the world matrices of dirty nodes and everything below them.
+    pub fn update(&mut self) {
+        let mut stack = self
+            .roots
+            .iter()
+            .map(|&root| (root, None, false))
+            .collect::<Vec<_>>();
+        while let Some((id, parent_world, parent_changed)) = stack.pop() {
+            let node = self.node_mut(id);
+            let changed = node.dirty || parent_changed;
+            if changed {
+                let local = node.transform.matrix();
+                node.world = match parent_world {
+                    Some(parent) => parent * local,
+                    None => local,
+                };
+                node.dirty = false;
+            }
+            let world = node.world;
+            stack.extend(
+                node.children
+                    .iter()
+                    .map(|&child| (child, Some(world), changed)),
+            );
+        }
+    }
+
+    /// Updates world matrices, then visits every node that has an item, parents before children.
+    pub fn traverse(&mut self, mut f: impl FnMut(NodeId, mats::Mat4<f32>, &T)) {
+        self.update();
+        let mut stack = self.roots.iter().rev().copied().collect::<Vec<_>>();
+        while let Some(id) = stack.pop() {
+            let node = self.node(id);
+            if let Some(item) = &node.item {
+                f(id, node.world, item);
+            }
+            stack.extend(node.children.iter().rev());
+        }
+    }
+
+    fn detach(&mut self, id: NodeId) {
+        let siblings = match self.node(id).parent {
+            Some(parent) => &mut self.node_mut(parent).children,
+            None => &mut self.roots,
+        };
+        siblings.retain(|&sibling| sibling != id);
+    }
+
+    fn node(&self, id: NodeId) -> &Node<T> {
+        let slot = &self.nodes[id.index as usize];
+        assert!(slot.generation == id.generation, "node was removed");
+        slot.node.as_ref().expect("node was removed")
+    }
+
+    fn node_mut(&mut self, id: NodeId) -> &mut Node<T> {
+        let slot = &mut self.nodes[id.index as usize];
+        assert!(slot.generation == id.generation, "node was removed");
+        slot.node.as_mut().expect("node was removed")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn removed_ids_do_not_reach_reused_slots() {
+        let mut graph = SceneGraph::new();
+        let parent = graph.add(None, Transform::default(), Some("parent"));
+        let child = graph.add(Some(parent), Transform::default(), Some("child"));
+        graph.remove(parent);
+        assert!(!graph.contains(parent));
+        assert!(!graph.contains(child));
+
+        let reused = graph.add(None, Transform::default(), Some("reused"));
+        assert!(graph.contains(reused));
+        assert!(!graph.contains(child) && !graph.contains(parent));
+        assert_eq!(graph.item(reused), Some(&"reused"));
+    }
+
+    #[test]
+    #[should_panic(expected = "node was removed")]
+    fn stale_ids_panic() {
+        let mut graph = SceneGraph::<()>::new();
+        let first = graph.add(None, Transform::default(), None);
+        graph.remove(first);
+        graph.add(None, Transform::default(), None);
+        graph.transform(first);
+    }
+}
@@ -1,15 +1,13 @@
 #version 330
 
-in float x;
-in float y;
-in float z;
+#include "projection.glsl"
 
+in vec3 position;
 in vec2 tex_coord;
-out vec2 frag_tex_coord;
 
-uniform mat4 transform;
+out vec2 frag_tex_coord;
 
 void main() {
-    gl_Position = transform * vec4(x, y, z, 1.0);
+    gl_Position = project(position);
     frag_tex_coord = tex_coord;
 }
\ No newline at end of file
@@ -0,0 +1,78 @@
+//! Imports the hand-written `fixtures/scene.gltf`: a textured triangle with tangents and a
+//! triangle strip under a common root, a 16-bit PNG as the only image, and a node outside
+//! the scene.
+
+use animation::gltf_scene::{AlphaMode, GltfScene};
+
+const FIXTURE: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/tests/fixtures/scene.gltf");
+
+fn scene() -> GltfScene {
+    GltfScene::load(FIXTURE).unwrap()
+}
+
+#[test]
+fn load_and_from_slice_agree() {
+    let loaded = scene();
+    let sliced = GltfScene::from_slice(&std::fs::read(FIXTURE).unwrap()).unwrap();
+    assert_eq!(loaded.nodes.len(), sliced.nodes.len());
+    assert_eq!(
+        loaded.meshes[0].primitives[0].mesh.positions,
+        sliced.meshes[0].primitives[0].mesh.positions
+    );
+}
+
+#[test]
+fn flipping_v_flips_tangent_handedness() {
+    let scene = scene();
+    let primitive = &scene.meshes[0].primitives[0];
+    let mesh = &primitive.mesh;
+    assert_eq!(primitive.material, Some(0));
+    assert_eq!(mesh.indices, [0, 1, 2]);
+    assert_eq!(mesh.tex_coords, [[0.0, 1.0], [1.0, 1.0], [0.0, 0.0]]);
+    assert_eq!(mesh.normals, [[0.0, 0.0, 1.0]; 3]);
+    assert_eq!(mesh.tangents, [[1.0, 0.0, 0.0, -1.0]; 3]);
+}
+
+#[test]
+fn strips_become_counter_clockwise_triangles() {
+    let scene = scene();
+    let mesh = &scene.meshes[1].primitives[0].mesh;
+    assert_eq!(mesh.indices, [0, 1, 2, 2, 1, 3]);
+    for normal in &mesh.normals {
+        assert!((normal[2] - 1.0).abs() < 1e-5, "{normal:?}");
+    }
+}
+
+#[test]
+fn hierarchy_follows_the_default_scene() {
+    let scene = scene();
+    assert_eq!(scene.roots, [0]);
+    assert_eq!(scene.nodes[0].children, [1, 2]);
+    assert_eq!(scene.nodes[0].translation, [0.0, 0.0, -2.0]);
+    assert_eq!(scene.nodes[2].scale, [2.0, 2.0, 2.0]);
+    assert_eq!(scene.nodes[3].name.as_deref(), Some("Unused"));
+    // The unused node is not reachable from the scene.
+    assert_eq!(scene.instances().len(), 2);
+}
+
+#[test]
+fn material_reads_factors_and_alpha_mode() {
+    let scene = scene();
+    let material = &scene.materials[0];
+    assert_eq!(material.name.as_deref(), Some("Cutout"));
+    assert_eq!(material.base_color, [1.0, 0.5, 0.25, 1.0]);
+    assert_eq!(material.base_color_texture, Some(0));
+    assert_eq!(material.metallic, 0.0);
+    assert_eq!(material.roughness, 0.75);
+    assert_eq!(material.alpha_mode, AlphaMode::Mask(0.3));
+    assert!(material.double_sided);
+}
+
+#[test]
+fn sixteen_bit_images_are_narrowed() {
+    let scene = scene();
+    let image = &scene.images[0];
+    assert_eq!(image.dimensions(), (2, 1));
+    assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+    assert_eq!(image.get_pixel(1, 0).0, [0, 128, 255, 255]);
+}
@@ -0,0 +1,53 @@
+//! Renders every scene of the crate offscreen and compares it with its golden, see
+//! `common::golden`.
+
+use animation::{Drawable, Offscreen};
+use common::golden::{self, SIZE};
+
+#[path = "../src/main.rs"]
+#[allow(dead_code)]
+mod animation_scene;
+
+#[path = "../src/bin/camera.rs"]
+#[allow(dead_code)]
+mod camera;
+
+#[path = "../src/bin/crowd.rs"]
+#[allow(dead_code)]
+mod crowd;
+
+#[path = "../src/bin/solar.rs"]
+#[allow(dead_code)]
+mod solar;
+
+const GOLDENS: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/goldens");
+/// The scenes are captured 1.5 s in, at the 60 Hz `Offscreen::new` runs at.
+const FRAMES: usize = 90;
+
+fn check<T: Drawable>(name: &str, scene: T) {
+    let mut offscreen = Offscreen::new(scene, SIZE.0, SIZE.1).unwrap();
+    for _ in 1..FRAMES {
+        offscreen.render();
+    }
+    golden::assert_golden(GOLDENS, name, &offscreen.render());
+}
+
+#[test]
+fn animation_matches_golden() {
+    check("animation", animation_scene::Canvas::default());
+}
+
+#[test]
+fn camera_matches_golden() {
+    check("camera", camera::Canvas::new(None));
+}
+
+#[test]
+fn crowd_matches_golden() {
+    check("crowd", crowd::Canvas::new());
+}
+
+#[test]
+fn solar_matches_golden() {
+    check("solar", solar::Canvas::new());
+}
@@ -0,0 +1,38 @@
+use std::path::PathBuf;
+
+/// What `MyWindow` should write out from the next drawn frame.
+#[derive(Debug, Clone)]
+pub enum Capture {
+    Screenshot(PathBuf),
+    /// `frames` consecutive frames saved as `dir/frame_0000.png`, `dir/frame_0001.png`, ...
+    Sequence {
+        dir: PathBuf,
+        frames: usize,
+    },
+}
+
+pub(crate) struct Recording {
+    dir: PathBuf,
+    next: usize,
+    frames: usize,
+}
+
+impl Recording {
+    pub(crate) fn new(dir: PathBuf, frames: usize) -> Self {
+        Self {
+            dir,
+            next: 0,
+            frames,
+        }
+    }
+
+    /// Path for the next frame, `None` once the sequence is complete.
+    pub(crate) fn next_path(&mut self) -> Option<PathBuf> {
+        if self.next == self.frames {
+            return None;
+        }
+        let path = self.dir.join(format!("frame_{:04}.png", self.next));
+        self.next += 1;
+        Some(path)
+    }
+}
@@ -0,0 +1,193 @@
+use std::time::{Duration, Instant};
+
+/// Source of frame timing handed to `Drawable::draw` through `FrameContext`.
+pub trait Clock {
+    /// Advances to the next frame and returns the time since the previous one.
+    fn tick(&mut self) -> Duration;
+
+    /// Total time accumulated by `tick`.
+    fn elapsed(&self) -> Duration;
+}
+
+/// Wall-clock time from a monotonic `Instant`.
+pub struct RealClock {
+    start: Instant,
+    last: Instant,
+}
+
+impl RealClock {
+    pub fn new() -> Self {
+        let now = Instant::now();
+        Self {
+            start: now,
+            last: now,
+        }
+    }
+}
+
+impl Default for RealClock {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Clock for RealClock {
+    fn tick(&mut self) -> Duration {
+        let now = Instant::now();
+        let delta = now - self.last;
+        self.last = now;
+        delta
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.last - self.start
+    }
+}
+
+/// Advances by the same step every frame, for reproducible output.
+pub struct FixedClock {
+    step: Duration,
+    elapsed: Duration,
+}
+
+impl FixedClock {
+    pub fn new(step: Duration) -> Self {
+        Self {
+            step,
+            elapsed: Duration::ZERO,
+        }
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+}
+
+impl Clock for FixedClock {
+    fn tick(&mut self) -> Duration {
+        self.elapsed += self.step;
+        self.step
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+/// Wraps another clock to slow down, speed up or pause it.
+pub struct ScaledClock<C: Clock> {
+    inner: C,
+    scale: f32,
+    paused: bool,
+    elapsed: Duration,
+}
+
+impl<C: Clock> ScaledClock<C> {
+    /// Starts at normal speed if `scale` is rejected by [`set_scale`](Self::set_scale).
+    pub fn new(inner: C, scale: f32) -> Self {
+        let mut clock = Self {
+            inner,
+            scale: 1.0,
+            paused: false,
+            elapsed: Duration::ZERO,
+        };
+        clock.set_scale(scale);
+        clock
+    }
+
+    pub fn scale(&self) -> f32 {
+        self.scale
+    }
+
+    /// Ignores scales that are negative or not finite, which `Duration::mul_f32` cannot apply.
+    pub fn set_scale(&mut self, scale: f32) {
+        if scale.is_finite() && scale >= 0.0 {
+            self.scale = scale;
+        }
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn pause(&mut self) {
+        self.paused = true;
+    }
+
+    pub fn resume(&mut self) {
+        self.paused = false;
+    }
+}
+
+impl<C: Clock> Clock for ScaledClock<C> {
+    fn tick(&mut self) -> Duration {
+        // The inner clock keeps running while paused so resuming doesn't produce a huge delta.
+        let delta = self.inner.tick();
+        let delta = if self.paused {
+            Duration::ZERO
+        } else {
+            delta.mul_f32(self.scale)
+        };
+        self.elapsed += delta;
+        delta
+    }
+
+    fn elapsed(&self) -> Duration {
+        self.elapsed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn millis(ms: u64) -> Duration {
+        Duration::from_millis(ms)
+    }
+
+    #[test]
+    fn fixed_clock_advances_by_its_step() {
+        let mut clock = FixedClock::new(millis(10));
+        assert_eq!(clock.elapsed(), Duration::ZERO);
+        assert_eq!(clock.tick(), millis(10));
+        assert_eq!(clock.tick(), millis(10));
+        assert_eq!(clock.elapsed(), millis(20));
+    }
+
+    #[test]
+    fn scaled_clock_scales_and_accumulates() {
+        let mut clock = ScaledClock::new(FixedClock::new(millis(10)), 2.0);
+        assert_eq!(clock.tick(), millis(20));
+        clock.set_scale(0.5);
+        assert_eq!(clock.tick(), millis(5));
+        assert_eq!(clock.elapsed(), millis(25));
+        assert_eq!(clock.inner.elapsed(), millis(20));
+    }
+
+    #[test]
+    fn paused_clock_stands_still_and_resumes_with_one_step() {
+        let mut clock = ScaledClock::new(FixedClock::new(millis(10)), 1.0);
+        clock.tick();
+        clock.pause();
+        assert!(clock.is_paused());
+        assert_eq!(clock.tick(), Duration::ZERO);
+        assert_eq!(clock.tick(), Duration::ZERO);
+        clock.resume();
+        assert!(!clock.is_paused());
+        assert_eq!(clock.tick(), millis(10));
+        assert_eq!(clock.elapsed(), millis(20));
+    }
+
+    #[test]
+    fn unusable_scales_are_ignored() {
+        let mut clock = ScaledClock::new(FixedClock::new(millis(10)), f32::NAN);
+        assert_eq!(clock.scale(), 1.0);
+        for scale in [-1.0, f32::NAN, f32::INFINITY] {
+            clock.set_scale(scale);
+            assert_eq!(clock.scale(), 1.0);
+            assert_eq!(clock.tick(), millis(10));
+        }
+        clock.set_scale(0.0);
+        assert_eq!(clock.tick(), Duration::ZERO);
+    }
+}
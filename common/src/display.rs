@@ -0,0 +1,92 @@
+use std::{error::Error, num::NonZeroU32};
+
+use glium::{
+    Display, Program, ProgramCreationError,
+    backend::Facade,
+    glutin::{
+        config::{ConfigTemplateBuilder, GlConfig},
+        context::{ContextAttributesBuilder, NotCurrentGlContext},
+        display::{GetGlDisplay, GlDisplay},
+        surface::{SurfaceAttributesBuilder, WindowSurface},
+    },
+    program::ProgramCreationInput,
+    winit::{
+        dpi::LogicalSize, event_loop::EventLoop, raw_window_handle::HasWindowHandle, window::Window,
+    },
+};
+use glutin_winit::DisplayBuilder;
+
+/// Opens a window whose default framebuffer is sRGB, like `SimpleWindowBuilder` otherwise.
+///
+/// Programs compiled with [`linear_program`] write linear color, and glium enables
+/// `GL_FRAMEBUFFER_SRGB` for them, so the hardware encodes on write. Without an sRGB surface
+/// that encoding is silently skipped and everything comes out too dark.
+pub fn create_display(
+    event_loop: &EventLoop<()>,
+    title: &str,
+) -> Result<(Window, Display<WindowSurface>), Box<dyn Error>> {
+    let attributes = Window::default_attributes()
+        .with_title(title)
+        .with_inner_size(LogicalSize::new(800, 480));
+    let (window, config) = DisplayBuilder::new()
+        .with_window_attributes(Some(attributes))
+        .build(
+            event_loop,
+            ConfigTemplateBuilder::new().with_alpha_size(8),
+            |configs| {
+                configs
+                    .reduce(|best, config| {
+                        if config.srgb_capable() && !best.srgb_capable() {
+                            config
+                        } else {
+                            best
+                        }
+                    })
+                    .expect("no GL config available")
+            },
+        )?;
+    let window = window.ok_or("failed to create the window")?;
+    if !config.srgb_capable() {
+        eprintln!("no sRGB-capable framebuffer available, colors will be too dark");
+    }
+
+    let handle = window.window_handle()?.as_raw();
+    let (width, height): (u32, u32) = window.inner_size().into();
+    let surface_attributes = SurfaceAttributesBuilder::<WindowSurface>::new()
+        .with_srgb(Some(true))
+        .build(
+            handle,
+            NonZeroU32::new(width).ok_or("window has zero width")?,
+            NonZeroU32::new(height).ok_or("window has zero height")?,
+        );
+    let gl_display = config.display();
+    let surface = unsafe { gl_display.create_window_surface(&config, &surface_attributes) }?;
+    let context_attributes = ContextAttributesBuilder::new().build(Some(handle));
+    let context = unsafe { gl_display.create_context(&config, &context_attributes) }?
+        .make_current(&surface)?;
+    let display = Display::from_context_surface(context, surface)?;
+    Ok((window, display))
+}
+
+/// Compiles a program whose fragment shader writes linear color, so glium enables
+/// `GL_FRAMEBUFFER_SRGB` while it draws. `Program::from_source` assumes the shader already
+/// encodes sRGB itself and leaves the conversion off.
+pub fn linear_program<F: Facade>(
+    facade: &F,
+    vertex_shader: &str,
+    fragment_shader: &str,
+) -> Result<Program, ProgramCreationError> {
+    Program::new(
+        facade,
+        ProgramCreationInput::SourceCode {
+            vertex_shader,
+            tessellation_control_shader: None,
+            tessellation_evaluation_shader: None,
+            geometry_shader: None,
+            fragment_shader,
+            transform_feedback_varyings: None,
+            outputs_srgb: false,
+            uses_point_size: false,
+        },
+    )
+}
@@ -0,0 +1,433 @@
+//! Entities with components stored per type, updated by behaviors and systems.
+//!
+//! Scenes keep a [`World`] and hand it out from `Drawable::world`, so `MyWindow` and
+//! `Offscreen` run its behaviors and systems on every update step, and call [`World::draw`]
+//! from `Drawable::draw`. Every entity with a [`Transform`], [`MeshRef`] and
+//! [`Material`] is drawn.
+//!
+//! [`Material`]: crate::Material
+
+use std::{
+    any::{Any, TypeId},
+    collections::HashMap,
+    time::Duration,
+};
+
+use glium::{
+    Surface,
+    uniforms::{UniformValue, Uniforms},
+};
+
+use crate::{FrameContext, InputState, Material, Transform};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct Entity {
+    index: u32,
+    generation: u32,
+}
+
+/// Name of the vertex and index buffers in [`Resources`](crate::Resources).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct MeshRef(pub &'static str);
+
+/// Per-entity logic, run once per update step before the systems.
+pub trait Behavior: 'static {
+    fn update(&mut self, entity: Entity, world: &mut World, dt: Duration, input: &InputState);
+}
+
+impl<F> Behavior for F
+where
+    F: FnMut(Entity, &mut World, Duration, &InputState) + 'static,
+{
+    fn update(&mut self, entity: Entity, world: &mut World, dt: Duration, input: &InputState) {
+        self(entity, world, dt, input)
+    }
+}
+
+type System = Box<dyn FnMut(&mut World, Duration, &InputState)>;
+
+trait AnyStorage {
+    fn remove(&mut self, index: usize);
+    fn as_any(&self) -> &dyn Any;
+    fn as_any_mut(&mut self) -> &mut dyn Any;
+}
+
+struct Storage<T>(Vec<Option<T>>);
+
+impl<T: 'static> AnyStorage for Storage<T> {
+    fn remove(&mut self, index: usize) {
+        if let Some(slot) = self.0.get_mut(index) {
+            *slot = None;
+        }
+    }
+
+    fn as_any(&self) -> &dyn Any {
+        self
+    }
+
+    fn as_any_mut(&mut self) -> &mut dyn Any {
+        self
+    }
+}
+
+#[derive(Default)]
+pub struct World {
+    generations: Vec<u32>,
+    alive: Vec<bool>,
+    free: Vec<u32>,
+    storages: HashMap<TypeId, Box<dyn AnyStorage>>,
+    behaviors: Vec<Vec<Box<dyn Behavior>>>,
+    systems: Vec<System>,
+}
+
+impl World {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn spawn(&mut self) -> Entity {
+        let index = match self.free.pop() {
+            Some(index) => index,
+            None => {
+                self.generations.push(0);
+                self.alive.push(false);
+                self.behaviors.push(Vec::new());
+                self.generations.len() as u32 - 1
+            }
+        };
+        self.alive[index as usize] = true;
+        Entity {
+            index,
+            generation: self.generations[index as usize],
+        }
+    }
+
+    /// Removes the entity and all its components, stale handles to it stop resolving.
+    pub fn despawn(&mut self, entity: Entity) {
+        if !self.is_alive(entity) {
+            return;
+        }
+        let index = entity.index as usize;
+        for storage in self.storages.values_mut() {
+            storage.remove(index);
+        }
+        self.behaviors[index].clear();
+        self.alive[index] = false;
+        self.generations[index] += 1;
+        self.free.push(entity.index);
+    }
+
+    pub fn is_alive(&self, entity: Entity) -> bool {
+        let index = entity.index as usize;
+        self.alive.get(index) == Some(&true) && self.generations[index] == entity.generation
+    }
+
+    pub fn entities(&self) -> impl Iterator<Item = Entity> + '_ {
+        (0..self.alive.len())
+            .filter(|&index| self.alive[index])
+            .map(|index| Entity {
+                index: index as u32,
+                generation: self.generations[index],
+            })
+    }
+
+    /// Adds or replaces the entity's component of type `T`.
+    ///
+    /// # Panics
+    ///
+    /// If the entity was despawned.
+    pub fn insert<T: 'static>(&mut self, entity: Entity, component: T) {
+        assert!(self.is_alive(entity), "entity {entity:?} was despawned");
+        let storage = self.storage_mut::<T>();
+        let index = entity.index as usize;
+        if storage.0.len() <= index {
+            storage.0.resize_with(index + 1, || None);
+        }
+        storage.0[index] = Some(component);
+    }
+
+    pub fn remove<T: 'static>(&mut self, entity: Entity) -> Option<T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.storage_mut::<T>()
+            .0
+            .get_mut(entity.index as usize)?
+            .take()
+    }
+
+    pub fn get<T: 'static>(&self, entity: Entity) -> Option<&T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.storage::<T>()?.0.get(entity.index as usize)?.as_ref()
+    }
+
+    pub fn get_mut<T: 'static>(&mut self, entity: Entity) -> Option<&mut T> {
+        if !self.is_alive(entity) {
+            return None;
+        }
+        self.storages
+            .get_mut(&TypeId::of::<T>())?
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()?
+            .0
+            .get_mut(entity.index as usize)?
+            .as_mut()
+    }
+
+    /// Entities that have a `T`, with it.
+    pub fn query<T: 'static>(&self) -> impl Iterator<Item = (Entity, &T)> + '_ {
+        let slots = self
+            .storage::<T>()
+            .map(|storage| &storage.0[..])
+            .unwrap_or(&[]);
+        slots.iter().enumerate().filter_map(|(index, slot)| {
+            let component = slot.as_ref()?;
+            let entity = Entity {
+                index: index as u32,
+                generation: self.generations[index],
+            };
+            Some((entity, component))
+        })
+    }
+
+    /// Runs `f` on every entity that has a `T`.
+    pub fn for_each_mut<T: 'static>(&mut self, mut f: impl FnMut(Entity, &mut T)) {
+        let generations = &self.generations;
+        let Some(storage) = self
+            .storages
+            .get_mut(&TypeId::of::<T>())
+            .and_then(|storage| storage.as_any_mut().downcast_mut::<Storage<T>>())
+        else {
+            return;
+        };
+        for (index, slot) in storage.0.iter_mut().enumerate() {
+            if let Some(component) = slot {
+                let entity = Entity {
+                    index: index as u32,
+                    generation: generations[index],
+                };
+                f(entity, component);
+            }
+        }
+    }
+
+    pub fn add_behavior(&mut self, entity: Entity, behavior: impl Behavior) {
+        assert!(self.is_alive(entity), "entity {entity:?} was despawned");
+        self.behaviors[entity.index as usize].push(Box::new(behavior));
+    }
+
+    /// Registers a system, systems run in the order they were added.
+    pub fn add_system(&mut self, system: impl FnMut(&mut World, Duration, &InputState) + 'static) {
+        self.systems.push(Box::new(system));
+    }
+
+    /// Runs every entity's behaviors, then the systems. Called on every step for the world a
+    /// scene returns from `Drawable::world`.
+    pub fn update(&mut self, dt: Duration, input: &InputState) {
+        for entity in self.entities().collect::<Vec<_>>() {
+            // An earlier behavior may have despawned it, and its slot may even be reused.
+            if !self.is_alive(entity) {
+                continue;
+            }
+            let index = entity.index as usize;
+            // Taken out so behaviors can borrow the world, including adding behaviors.
+            let mut behaviors = std::mem::take(&mut self.behaviors[index]);
+            for behavior in &mut behaviors {
+                behavior.update(entity, self, dt, input);
+            }
+            if self.is_alive(entity) {
+                behaviors.append(&mut self.behaviors[index]);
+                self.behaviors[index] = behaviors;
+            }
+        }
+
+        let mut systems = std::mem::take(&mut self.systems);
+        for system in &mut systems {
+            system(self, dt, input);
+        }
+        systems.append(&mut self.systems);
+        self.systems = systems;
+    }
+
+    /// Draws every entity with a transform, mesh and material. Besides `shared`, each draw
+    /// gets `transform`, the model-view-projection matrix, and `model`.
+    pub fn draw<S: Surface, U: Uniforms>(
+        &self,
+        target: &mut S,
+        context: &FrameContext,
+        view_projection: mats::Mat4<f32>,
+        shared: &U,
+    ) {
+        for (entity, mesh) in self.query::<MeshRef>() {
+            let (Some(transform), Some(material)) =
+                (self.get::<Transform>(entity), self.get::<Material>(entity))
+            else {
+                continue;
+            };
+            let resources = context.resources;
+            let model = transform.matrix();
+            let uniforms = EntityUniforms {
+                shared,
+                entity: glium::uniform! {
+                    transform: view_projection * model,
+                    model: model,
+                },
+            };
+            material
+                .draw(
+                    target,
+                    resources,
+                    resources.vertex_buffer(mesh.0),
+                    resources.index_buffer(mesh.0),
+                    uniforms,
+                )
+                .unwrap();
+        }
+    }
+
+    fn storage<T: 'static>(&self) -> Option<&Storage<T>> {
+        self.storages
+            .get(&TypeId::of::<T>())?
+            .as_any()
+            .downcast_ref::<Storage<T>>()
+    }
+
+    fn storage_mut<T: 'static>(&mut self) -> &mut Storage<T> {
+        self.storages
+            .entry(TypeId::of::<T>())
+            .or_insert_with(|| Box::new(Storage::<T>(Vec::new())))
+            .as_any_mut()
+            .downcast_mut::<Storage<T>>()
+            .expect("storage is keyed by its component type")
+    }
+}
+
+/// The uniforms shared by every draw of [`World::draw`], followed by one entity's.
+struct EntityUniforms<'u, U, E> {
+    shared: &'u U,
+    entity: E,
+}
+
+impl<U: Uniforms, E: Uniforms> Uniforms for EntityUniforms<'_, U, E> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        self.shared.visit_values(&mut output);
+        self.entity.visit_values(output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::{cell::RefCell, rc::Rc};
+
+    use super::*;
+    use crate::{Drawable, Timestep, window::run_updates};
+
+    #[test]
+    fn despawned_handles_stop_resolving_after_reuse() {
+        let mut world = World::new();
+        let first = world.spawn();
+        world.insert(first, 1u32);
+        world.despawn(first);
+        assert!(!world.is_alive(first));
+        assert_eq!(world.get::<u32>(first), None);
+
+        let second = world.spawn();
+        assert_eq!(second.index, first.index);
+        assert_ne!(second.generation, first.generation);
+        assert_eq!(world.get::<u32>(second), None);
+        world.insert(second, 2u32);
+        assert_eq!(world.get::<u32>(first), None);
+        assert_eq!(world.remove::<u32>(first), None);
+        assert_eq!(world.get::<u32>(second), Some(&2));
+        assert_eq!(world.entities().collect::<Vec<_>>(), [second]);
+    }
+
+    #[test]
+    fn despawn_removes_every_component() {
+        let mut world = World::new();
+        let entity = world.spawn();
+        let other = world.spawn();
+        world.insert(entity, 1u32);
+        world.insert(entity, "name");
+        world.insert(other, 2u32);
+        world.despawn(entity);
+        world.despawn(entity);
+        assert_eq!(world.query::<u32>().collect::<Vec<_>>(), [(other, &2)]);
+        assert_eq!(world.query::<&str>().count(), 0);
+    }
+
+    #[test]
+    fn behaviors_skip_entities_despawned_earlier_in_the_step() {
+        let mut world = World::new();
+        let killer = world.spawn();
+        let victim = world.spawn();
+        let seen = Rc::new(RefCell::new(Vec::new()));
+        // Despawns the victim and hands its slot to a new entity with a behavior of its own.
+        let mut replaced = false;
+        let record = seen.clone();
+        world.add_behavior(
+            killer,
+            move |_: Entity, world: &mut World, _: Duration, _: &InputState| {
+                if replaced {
+                    return;
+                }
+                replaced = true;
+                world.despawn(victim);
+                let replacement = world.spawn();
+                let record = record.clone();
+                world.add_behavior(
+                    replacement,
+                    move |entity: Entity, _: &mut World, _: Duration, _: &InputState| {
+                        record.borrow_mut().push(entity);
+                    },
+                );
+            },
+        );
+
+        let input = InputState::new();
+        world.update(Duration::from_millis(10), &input);
+        assert!(seen.borrow().is_empty());
+        world.update(Duration::from_millis(10), &input);
+        let replacement = world.entities().last().unwrap();
+        assert_ne!(replacement, victim);
+        assert_eq!(*seen.borrow(), [replacement]);
+    }
+
+    /// Keeps its count in a world system.
+    struct Systems {
+        world: World,
+    }
+
+    impl Drawable for Systems {
+        fn draw<S: Surface>(&mut self, _target: &mut S, _context: &FrameContext) {}
+
+        fn world(&mut self) -> Option<&mut World> {
+            Some(&mut self.world)
+        }
+    }
+
+    #[test]
+    fn world_systems_run_every_step() {
+        let mut world = World::new();
+        let counter = world.spawn();
+        world.insert(counter, 0u32);
+        world.add_system(
+            move |world: &mut World, _dt: Duration, _input: &InputState| {
+                *world.get_mut::<u32>(counter).unwrap() += 1;
+            },
+        );
+        let mut systems = Systems { world };
+        let mut timestep = Timestep::new(Duration::from_millis(10));
+        let mut input = InputState::new();
+
+        run_updates(
+            &mut systems,
+            &mut timestep,
+            Duration::from_millis(30),
+            &mut input,
+        );
+        assert_eq!(systems.world.get::<u32>(counter), Some(&3));
+    }
+}
@@ -0,0 +1,479 @@
+//! Shader sources baked into the binary, and a preprocessor for `#include` and `#define`.
+
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    error::Error,
+    fmt::{self, Write},
+    fs,
+};
+
+use glium::{Program, ProgramCreationError, backend::Facade, program::ShaderType};
+
+use crate::{lighting::MAX_LIGHTS, linear_program};
+
+/// A shader source baked into the binary together with the file it came from.
+///
+/// Usually created with the [`shader!`](crate::shader) macro.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ShaderFile {
+    pub source: &'static str,
+    pub path: &'static str,
+}
+
+impl ShaderFile {
+    pub const fn new(source: &'static str, path: &'static str) -> Self {
+        Self { source, path }
+    }
+
+    /// The file as it is now on disk, or the baked source if it cannot be read.
+    pub fn read(&self) -> String {
+        fs::read_to_string(self.path).unwrap_or_else(|err| {
+            eprintln!(
+                "failed to read {}: {err}, using the built-in source",
+                self.path
+            );
+            self.source.to_owned()
+        })
+    }
+}
+
+/// Bakes the shader at `path`, relative to the calling crate's manifest directory, into the
+/// binary and remembers where it came from for hot-reloading.
+///
+/// ```ignore
+/// let vertex = common::shader!("src/shaders/lit.vert");
+/// ```
+#[macro_export]
+macro_rules! shader {
+    ($path:literal) => {
+        $crate::ShaderFile::new(
+            include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/", $path)),
+            concat!(env!("CARGO_MANIFEST_DIR"), "/", $path),
+        )
+    };
+}
+
+#[derive(Debug)]
+pub enum PreprocessError {
+    /// `#include` of a name that was never registered with [`Preprocessor::add_include`].
+    UnknownInclude {
+        file: &'static str,
+        line: u32,
+        include: String,
+    },
+    RecursiveInclude {
+        file: &'static str,
+        line: u32,
+        include: String,
+    },
+    Syntax {
+        file: &'static str,
+        line: u32,
+        message: String,
+    },
+}
+
+impl fmt::Display for PreprocessError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::UnknownInclude {
+                file,
+                line,
+                include,
+            } => write!(f, "{file}:{line}: unknown include \"{include}\""),
+            Self::RecursiveInclude {
+                file,
+                line,
+                include,
+            } => write!(f, "{file}:{line}: \"{include}\" includes itself"),
+            Self::Syntax {
+                file,
+                line,
+                message,
+            } => write!(f, "{file}:{line}: {message}"),
+        }
+    }
+}
+
+impl Error for PreprocessError {}
+
+#[derive(Debug)]
+pub enum ShaderError {
+    Preprocess(PreprocessError),
+    /// Compile log with locations pointing into the original files.
+    Compile {
+        stage: &'static str,
+        log: String,
+    },
+    Program(ProgramCreationError),
+}
+
+impl fmt::Display for ShaderError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Preprocess(err) => write!(f, "failed to preprocess shader: {err}"),
+            Self::Compile { stage, log } => write!(f, "failed to compile {stage} shader:\n{log}"),
+            Self::Program(err) => write!(f, "failed to create program: {err}"),
+        }
+    }
+}
+
+impl Error for ShaderError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Preprocess(err) => Some(err),
+            Self::Compile { .. } => None,
+            Self::Program(err) => Some(err),
+        }
+    }
+}
+
+impl From<PreprocessError> for ShaderError {
+    fn from(err: PreprocessError) -> Self {
+        Self::Preprocess(err)
+    }
+}
+
+/// Expands `#include "name"` and injects `#define`s into shader sources.
+///
+/// Includes are looked up by name among the registered [`ShaderFile`]s, so they are baked
+/// into the binary like the shaders themselves. A file containing `#pragma once` is expanded
+/// only the first time. Every file gets its own GLSL source string number through `#line`,
+/// which [`Preprocessed::map_log`] turns back into paths.
+///
+/// The default preprocessor knows the shared `projection.glsl` and `lighting.glsl`, and
+/// defines `MAX_LIGHTS` for the latter.
+pub struct Preprocessor {
+    includes: HashMap<&'static str, ShaderFile>,
+    defines: Vec<(String, String)>,
+}
+
+impl Default for Preprocessor {
+    fn default() -> Self {
+        let mut preprocessor = Self::new();
+        preprocessor.add_include(
+            "projection.glsl",
+            crate::shader!("src/shaders/projection.glsl"),
+        );
+        preprocessor.add_include("lighting.glsl", crate::shader!("src/shaders/lighting.glsl"));
+        preprocessor.define("MAX_LIGHTS", MAX_LIGHTS);
+        preprocessor
+    }
+}
+
+impl Preprocessor {
+    /// No includes or defines.
+    pub fn new() -> Self {
+        Self {
+            includes: HashMap::new(),
+            defines: Vec::new(),
+        }
+    }
+
+    pub fn add_include(&mut self, name: &'static str, file: ShaderFile) {
+        self.includes.insert(name, file);
+    }
+
+    /// Defines `name` in every shader processed from now on.
+    pub fn define(&mut self, name: impl Into<String>, value: impl fmt::Display) {
+        let name = name.into();
+        let value = value.to_string();
+        match self.defines.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.defines.push((name, value)),
+        }
+    }
+
+    /// Expands `shader` with the preprocessor's defines and `defines`, which take precedence
+    /// over the preprocessor's own. With `from_disk` the files are read from their paths
+    /// rather than the baked sources.
+    pub fn process(
+        &self,
+        shader: ShaderFile,
+        defines: &[(&str, &str)],
+        from_disk: bool,
+    ) -> Result<Preprocessed, PreprocessError> {
+        let mut out = Preprocessed {
+            source: String::new(),
+            files: vec![shader],
+        };
+        let source = load(shader, from_disk);
+
+        // `#version` has to stay first, the defines go right after it.
+        let mut skip = 0;
+        if let Some(line) = source.lines().next()
+            && line.trim_start().starts_with("#version")
+        {
+            out.source.push_str(line);
+            out.source.push('\n');
+            skip = 1;
+        }
+        let own = self
+            .defines
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .filter(|(name, _)| !defines.iter().any(|(n, _)| n == name));
+        for (name, value) in own.chain(defines.iter().copied()) {
+            writeln!(out.source, "#define {name} {value}").unwrap();
+        }
+        writeln!(out.source, "#line {} 0", skip + 1).unwrap();
+
+        let mut stack = vec![0];
+        self.expand(&source, skip, &mut out, &mut stack, from_disk)?;
+        Ok(out)
+    }
+
+    /// Appends the lines of the file on top of `stack` from `skip` on.
+    fn expand(
+        &self,
+        source: &str,
+        skip: usize,
+        out: &mut Preprocessed,
+        stack: &mut Vec<usize>,
+        from_disk: bool,
+    ) -> Result<(), PreprocessError> {
+        let current = *stack.last().unwrap();
+        let file = out.files[current].path;
+        for (i, line) in source.lines().enumerate().skip(skip) {
+            let number = i as u32 + 1;
+            let directive = line.trim_start();
+            if is_pragma_once(directive) {
+                out.source.push('\n');
+                continue;
+            }
+            let Some(rest) = directive.strip_prefix("#include") else {
+                out.source.push_str(line);
+                out.source.push('\n');
+                continue;
+            };
+
+            let name = quoted(rest).ok_or_else(|| PreprocessError::Syntax {
+                file,
+                line: number,
+                message: "expected #include \"name\"".to_owned(),
+            })?;
+            let include =
+                *self
+                    .includes
+                    .get(name)
+                    .ok_or_else(|| PreprocessError::UnknownInclude {
+                        file,
+                        line: number,
+                        include: name.to_owned(),
+                    })?;
+            let seen = out.files.iter().position(|known| *known == include);
+            if seen.is_some_and(|index| stack.contains(&index)) {
+                return Err(PreprocessError::RecursiveInclude {
+                    file,
+                    line: number,
+                    include: name.to_owned(),
+                });
+            }
+            let text = load(include, from_disk);
+            if seen.is_some() && text.lines().any(|line| is_pragma_once(line.trim_start())) {
+                out.source.push('\n');
+                continue;
+            }
+            let index = seen.unwrap_or_else(|| {
+                out.files.push(include);
+                out.files.len() - 1
+            });
+
+            writeln!(out.source, "#line 1 {index}").unwrap();
+            stack.push(index);
+            self.expand(&text, 0, out, stack, from_disk)?;
+            stack.pop();
+            writeln!(out.source, "#line {} {current}", number + 1).unwrap();
+        }
+        Ok(())
+    }
+}
+
+/// A shader with its includes expanded, and the files its source string numbers refer to.
+#[derive(Debug)]
+pub struct Preprocessed {
+    source: String,
+    files: Vec<ShaderFile>,
+}
+
+impl Preprocessed {
+    pub fn source(&self) -> &str {
+        &self.source
+    }
+
+    /// The processed shader first, then its includes.
+    pub fn files(&self) -> &[ShaderFile] {
+        &self.files
+    }
+
+    /// Rewrites the `0:12` and `0(12)` locations drivers put in compile logs to `path:12`.
+    pub fn map_log(&self, log: &str) -> String {
+        log.lines()
+            .map(|line| self.map_line(line))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn map_line(&self, line: &str) -> String {
+        let bytes = line.as_bytes();
+        let digits = |from: usize| {
+            bytes[from..]
+                .iter()
+                .take_while(|byte| byte.is_ascii_digit())
+                .count()
+        };
+        for start in 0..bytes.len() {
+            if !bytes[start].is_ascii_digit()
+                || start > 0 && bytes[start - 1].is_ascii_alphanumeric()
+            {
+                continue;
+            }
+            let index_end = start + digits(start);
+            let close = match bytes.get(index_end) {
+                Some(b':') => None,
+                Some(b'(') => Some(b')'),
+                _ => continue,
+            };
+            let number_start = index_end + 1;
+            let number_end = number_start + digits(number_start);
+            if number_end == number_start
+                || close.is_some_and(|close| bytes.get(number_end) != Some(&close))
+            {
+                continue;
+            }
+            let end = number_end + close.map_or(0, |_| 1);
+            let (Ok(index), Ok(number)) = (
+                line[start..index_end].parse::<usize>(),
+                line[number_start..number_end].parse::<u32>(),
+            ) else {
+                continue;
+            };
+            let Some(file) = self.files.get(index) else {
+                continue;
+            };
+            return format!("{}{}:{number}{}", &line[..start], file.path, &line[end..]);
+        }
+        line.to_owned()
+    }
+}
+
+/// Compiles a program from preprocessed sources, mapping compile errors to the original files.
+pub fn compile_program<F: Facade>(
+    facade: &F,
+    vertex: &Preprocessed,
+    fragment: &Preprocessed,
+) -> Result<Program, ShaderError> {
+    linear_program(facade, vertex.source(), fragment.source()).map_err(|err| match err {
+        ProgramCreationError::CompilationError(log, ShaderType::Vertex) => ShaderError::Compile {
+            stage: "vertex",
+            log: vertex.map_log(&log),
+        },
+        ProgramCreationError::CompilationError(log, ShaderType::Fragment) => ShaderError::Compile {
+            stage: "fragment",
+            log: fragment.map_log(&log),
+        },
+        err => ShaderError::Program(err),
+    })
+}
+
+fn load(file: ShaderFile, from_disk: bool) -> Cow<'static, str> {
+    if from_disk {
+        Cow::Owned(file.read())
+    } else {
+        Cow::Borrowed(file.source)
+    }
+}
+
+fn is_pragma_once(directive: &str) -> bool {
+    let mut words = directive.split_whitespace();
+    words.next() == Some("#pragma") && words.next() == Some("once")
+}
+
+/// The name in `"name"`, ignoring surrounding whitespace.
+fn quoted(rest: &str) -> Option<&str> {
+    rest.trim().strip_prefix('"')?.strip_suffix('"')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAIN: ShaderFile = ShaderFile::new(
+        "#version 330\n#include \"a.glsl\"\nvoid main() {}\n",
+        "main.frag",
+    );
+    const A: ShaderFile = ShaderFile::new("#pragma once\nfloat a;\n", "a.glsl");
+
+    fn preprocessor() -> Preprocessor {
+        let mut preprocessor = Preprocessor::new();
+        preprocessor.add_include("a.glsl", A);
+        preprocessor.define("LIGHTS", 4);
+        preprocessor
+    }
+
+    #[test]
+    fn includes_get_their_own_source_string() {
+        let processed = preprocessor().process(MAIN, &[], false).unwrap();
+        assert_eq!(
+            processed.source(),
+            "#version 330\n\
+             #define LIGHTS 4\n\
+             #line 2 0\n\
+             #line 1 1\n\
+             \n\
+             float a;\n\
+             #line 3 0\n\
+             void main() {}\n"
+        );
+        assert_eq!(processed.files(), [MAIN, A]);
+    }
+
+    #[test]
+    fn pragma_once_keeps_line_numbers() {
+        let main = ShaderFile::new(
+            "#version 330\n#include \"a.glsl\"\n#include \"a.glsl\"\nvoid main() {}\n",
+            "main.frag",
+        );
+        let processed = preprocessor().process(main, &[], false).unwrap();
+        let lines: Vec<_> = processed.source().lines().collect();
+        // The second include is an empty line, so `main` stays on line 4 of file 0.
+        assert_eq!(lines[6..], ["#line 3 0", "", "void main() {}"]);
+        assert_eq!(processed.files(), [main, A]);
+    }
+
+    #[test]
+    fn call_defines_override_the_preprocessor() {
+        let processed = preprocessor()
+            .process(MAIN, &[("LIGHTS", "8"), ("UNTEXTURED", "1")], false)
+            .unwrap();
+        let defines: Vec<_> = processed
+            .source()
+            .lines()
+            .filter(|line| line.starts_with("#define"))
+            .collect();
+        assert_eq!(defines, ["#define LIGHTS 8", "#define UNTEXTURED 1"]);
+    }
+
+    #[test]
+    fn unknown_includes_report_their_line() {
+        let err = Preprocessor::new().process(MAIN, &[], false).unwrap_err();
+        assert_eq!(err.to_string(), "main.frag:2: unknown include \"a.glsl\"");
+    }
+
+    #[test]
+    fn map_log_rewrites_driver_locations() {
+        let processed = preprocessor().process(MAIN, &[], false).unwrap();
+        let log = "0:3(14): error: syntax error\n\
+                   1(2) : error C0000: undefined\n\
+                   ERROR: 2:5: out of range\n\
+                   vec2:1 is not a location";
+        assert_eq!(
+            processed.map_log(log),
+            "main.frag:3(14): error: syntax error\n\
+             a.glsl:2 : error C0000: undefined\n\
+             ERROR: 2:5: out of range\n\
+             vec2:1 is not a location"
+        );
+    }
+}
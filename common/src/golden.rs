@@ -0,0 +1,175 @@
+//! Golden-image checks for the lessons' scene tests.
+//!
+//! Each crate's `tests/golden.rs` renders its scenes offscreen at [`SIZE`] and compares them
+//! with the committed `goldens/<name>.png`. A missing golden fails the test, `GOLDEN_UPDATE=1`
+//! records it, or re-records it after an intended change. On a mismatch the actual frame and a
+//! diff image are written next to the golden.
+//!
+//! ```sh
+//! GOLDEN_UPDATE=1 cargo test --test golden
+//! ```
+
+use std::{
+    error::Error,
+    fmt,
+    path::{Path, PathBuf},
+};
+
+use image::{Rgba, RgbaImage};
+
+pub const SIZE: (u32, u32) = (256, 256);
+/// Largest per-channel difference for a pixel to still count as equal.
+pub const TOLERANCE: u8 = 2;
+
+#[derive(Debug)]
+pub enum GoldenError {
+    Image(image::ImageError),
+    Missing(PathBuf),
+    SizeMismatch {
+        expected: (u32, u32),
+        actual: (u32, u32),
+    },
+    Mismatch {
+        pixels: usize,
+        diff: PathBuf,
+    },
+}
+
+impl fmt::Display for GoldenError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Image(err) => write!(f, "golden image i/o failed: {err}"),
+            Self::Missing(golden) => write!(
+                f,
+                "{} does not exist, run with GOLDEN_UPDATE=1 to record it",
+                golden.display()
+            ),
+            Self::SizeMismatch { expected, actual } => write!(
+                f,
+                "golden is {}x{} but the scene rendered {}x{}",
+                expected.0, expected.1, actual.0, actual.1
+            ),
+            Self::Mismatch { pixels, diff } => write!(
+                f,
+                "{pixels} pixels differ from the golden, see {}",
+                diff.display()
+            ),
+        }
+    }
+}
+
+impl Error for GoldenError {}
+
+impl From<image::ImageError> for GoldenError {
+    fn from(err: image::ImageError) -> Self {
+        Self::Image(err)
+    }
+}
+
+fn updating() -> bool {
+    std::env::var_os("GOLDEN_UPDATE").is_some()
+}
+
+/// Compares `actual` with `dir/<name>.png`, or records it there with `GOLDEN_UPDATE` set.
+pub fn check(dir: impl AsRef<Path>, name: &str, actual: &RgbaImage) -> Result<(), GoldenError> {
+    let dir = dir.as_ref();
+    let golden = dir.join(format!("{name}.png"));
+    if updating() {
+        std::fs::create_dir_all(dir).map_err(image::ImageError::IoError)?;
+        actual.save(&golden)?;
+        println!("recorded {}", golden.display());
+        return Ok(());
+    }
+    if !golden.exists() {
+        return Err(GoldenError::Missing(golden));
+    }
+
+    let expected = image::open(&golden)?.to_rgba8();
+    let pixels = compare(&expected, actual)?;
+    if pixels == 0 {
+        return Ok(());
+    }
+    let diff = dir.join(format!("{name}.diff.png"));
+    actual.save(dir.join(format!("{name}.actual.png")))?;
+    diff_image(&expected, actual).save(&diff)?;
+    Err(GoldenError::Mismatch { pixels, diff })
+}
+
+/// [`check`] that panics with a readable message, for tests.
+pub fn assert_golden(dir: impl AsRef<Path>, name: &str, actual: &RgbaImage) {
+    if let Err(err) = check(dir, name, actual) {
+        panic!("golden `{name}`: {err}");
+    }
+}
+
+/// Counts the pixels that differ by more than [`TOLERANCE`] in any channel.
+pub fn compare(expected: &RgbaImage, actual: &RgbaImage) -> Result<usize, GoldenError> {
+    if expected.dimensions() != actual.dimensions() {
+        return Err(GoldenError::SizeMismatch {
+            expected: expected.dimensions(),
+            actual: actual.dimensions(),
+        });
+    }
+    Ok(expected
+        .pixels()
+        .zip(actual.pixels())
+        .filter(|(a, b)| !same(a, b))
+        .count())
+}
+
+/// Differing pixels in red over a dimmed copy of the golden.
+pub fn diff_image(expected: &RgbaImage, actual: &RgbaImage) -> RgbaImage {
+    RgbaImage::from_fn(expected.width(), expected.height(), |x, y| {
+        let a = expected.get_pixel(x, y);
+        if same(a, actual.get_pixel(x, y)) {
+            let [r, g, b, _] = a.0;
+            let grey = ((r as u16 + g as u16 + b as u16) / 12) as u8;
+            Rgba([grey, grey, grey, 255])
+        } else {
+            Rgba([255, 0, 0, 255])
+        }
+    })
+}
+
+fn same(a: &Rgba<u8>, b: &Rgba<u8>) -> bool {
+    a.0.iter().zip(b.0).all(|(a, b)| a.abs_diff(b) <= TOLERANCE)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn compare_allows_the_tolerance() {
+        let expected = RgbaImage::from_pixel(4, 4, Rgba([100, 100, 100, 255]));
+        let mut actual = expected.clone();
+        actual.put_pixel(0, 0, Rgba([100 + TOLERANCE, 100, 100, 255]));
+        assert_eq!(compare(&expected, &actual).unwrap(), 0);
+        actual.put_pixel(1, 0, Rgba([100, 100 - TOLERANCE - 1, 100, 255]));
+        assert_eq!(compare(&expected, &actual).unwrap(), 1);
+    }
+
+    #[test]
+    fn compare_rejects_other_sizes() {
+        let expected = RgbaImage::new(4, 4);
+        let actual = RgbaImage::new(4, 2);
+        assert!(matches!(
+            compare(&expected, &actual),
+            Err(GoldenError::SizeMismatch {
+                expected: (4, 4),
+                actual: (4, 2)
+            })
+        ));
+    }
+
+    #[test]
+    fn missing_golden_fails() {
+        if updating() {
+            return;
+        }
+        let dir = std::env::temp_dir().join("common-golden-missing");
+        let result = check(&dir, "nothing", &RgbaImage::new(1, 1));
+        assert!(matches!(result, Err(GoldenError::Missing(_))));
+        assert!(!dir.join("nothing.png").exists());
+    }
+}
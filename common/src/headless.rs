@@ -0,0 +1,142 @@
+//! Rendering without a display, for the offscreen hosts and the lessons' scene tests.
+
+use std::{
+    error::Error,
+    ffi::{CString, c_void},
+    ops::Deref,
+    rc::Rc,
+};
+
+use glium::{
+    SwapBuffersError, Texture2d,
+    backend::{Backend, Context, Facade},
+    debug::DebugCallbackBehavior,
+    framebuffer::SimpleFrameBuffer,
+    glutin::{
+        api::egl::{context::PossiblyCurrentContext, device::Device, display::Display},
+        config::{ConfigSurfaceTypes, ConfigTemplateBuilder},
+        context::{ContextApi, ContextAttributesBuilder, PossiblyCurrentGlContext, Version},
+        display::GlDisplay,
+    },
+    texture::{MipmapsOption, RawImage2d, SrgbFormat, SrgbTexture2d, UncompressedFloatFormat},
+};
+use image::RgbaImage;
+
+/// A glium context without a window or surface, see [`context`].
+pub struct Headless {
+    context: Rc<Context>,
+}
+
+impl Deref for Headless {
+    type Target = Context;
+
+    fn deref(&self) -> &Context {
+        &self.context
+    }
+}
+
+impl Facade for Headless {
+    fn get_context(&self) -> &Rc<Context> {
+        &self.context
+    }
+}
+
+/// An EGL context made current without a surface. Everything is drawn into framebuffer
+/// objects, so there is nothing to swap and the default framebuffer is never used.
+struct Surfaceless {
+    display: Display,
+    context: PossiblyCurrentContext,
+}
+
+unsafe impl Backend for Surfaceless {
+    fn swap_buffers(&self) -> Result<(), SwapBuffersError> {
+        Ok(())
+    }
+
+    unsafe fn get_proc_address(&self, symbol: &str) -> *const c_void {
+        let symbol = CString::new(symbol).unwrap();
+        self.display.get_proc_address(&symbol)
+    }
+
+    fn get_framebuffer_dimensions(&self) -> (u32, u32) {
+        (1, 1)
+    }
+
+    fn resize(&self, _new_size: (u32, u32)) {}
+
+    fn is_current(&self) -> bool {
+        self.context.is_current()
+    }
+
+    unsafe fn make_current(&self) {
+        self.context.make_current_surfaceless().unwrap();
+    }
+}
+
+/// An OpenGL 3.3 context on the first EGL device, current on this thread without a surface.
+/// Mesa's llvmpipe works fine.
+pub fn context() -> Result<Headless, Box<dyn Error>> {
+    let device = Device::query_devices()?
+        .next()
+        .ok_or("no EGL device available")?;
+    let display = unsafe { Display::with_device(&device, None) }?;
+    let template = ConfigTemplateBuilder::new()
+        .with_alpha_size(8)
+        .with_surface_type(ConfigSurfaceTypes::empty())
+        .build();
+    let config = unsafe { display.find_configs(template) }?
+        .next()
+        .ok_or("no EGL config available")?;
+    let attributes = ContextAttributesBuilder::new()
+        .with_context_api(ContextApi::OpenGl(Some(Version::new(3, 3))))
+        .build(None);
+    let context =
+        unsafe { display.create_context(&config, &attributes) }?.make_current_surfaceless()?;
+    let backend = Surfaceless { display, context };
+    let context = unsafe { Context::new(backend, true, DebugCallbackBehavior::default()) }?;
+    Ok(Headless { context })
+}
+
+/// Draws a single `width` x `height` frame into a color-only framebuffer and reads it back.
+pub fn render_frame<F>(width: u32, height: u32, draw: F) -> Result<RgbaImage, Box<dyn Error>>
+where
+    F: FnOnce(&Headless, &mut SimpleFrameBuffer),
+{
+    let context = context()?;
+    let color = Texture2d::empty_with_format(
+        &context,
+        UncompressedFloatFormat::U8U8U8U8,
+        MipmapsOption::NoMipmap,
+        width,
+        height,
+    )?;
+    let mut target = SimpleFrameBuffer::new(&context, &color)?;
+    draw(&context, &mut target);
+    Ok(to_rgba_image(color.read()))
+}
+
+/// Like [`render_frame`], into an sRGB framebuffer like the one
+/// [`create_display`](crate::create_display) opens.
+pub fn render_srgb_frame<F>(width: u32, height: u32, draw: F) -> Result<RgbaImage, Box<dyn Error>>
+where
+    F: FnOnce(&Headless, &mut SimpleFrameBuffer),
+{
+    let context = context()?;
+    let color = SrgbTexture2d::empty_with_format(
+        &context,
+        SrgbFormat::U8U8U8U8,
+        MipmapsOption::NoMipmap,
+        width,
+        height,
+    )?;
+    let mut target = SimpleFrameBuffer::new(&context, &color)?;
+    draw(&context, &mut target);
+    Ok(to_rgba_image(color.read()))
+}
+
+/// OpenGL rows start at the bottom, image rows at the top.
+pub fn to_rgba_image(raw: RawImage2d<u8>) -> RgbaImage {
+    let mut image = RgbaImage::from_raw(raw.width, raw.height, raw.data.into_owned()).unwrap();
+    image::imageops::flip_vertical_in_place(&mut image);
+    image
+}
@@ -0,0 +1,114 @@
+//! Shader hot-reload for development.
+//!
+//! Run a scene with `--hot-reload` and programs loaded through
+//! [`Resources::load_program_files`](crate::Resources::load_program_files) are read from disk
+//! instead of the copy baked into the binary. Their files, including everything they
+//! `#include`, are polled for changes and the program is recompiled in place. A program that
+//! fails to compile prints its log and the previous one stays in use, while the files of the
+//! failed attempt are watched for the fix.
+//!
+//! ```sh
+//! cargo run --bin camera -- --hot-reload
+//! ```
+
+use std::{
+    collections::HashMap,
+    fs,
+    path::Path,
+    time::{Duration, Instant, SystemTime},
+};
+
+use crate::ShaderFile;
+
+/// How often the watched files are checked.
+pub(crate) const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+pub(crate) fn requested() -> bool {
+    std::env::args().any(|arg| arg == "--hot-reload")
+}
+
+/// What a hot-reloadable program is built from.
+#[derive(Clone)]
+pub(crate) struct ProgramFiles {
+    pub(crate) vertex: ShaderFile,
+    pub(crate) fragment: ShaderFile,
+    pub(crate) defines: Vec<(String, String)>,
+}
+
+impl ProgramFiles {
+    pub(crate) fn defines(&self) -> Vec<(&str, &str)> {
+        self.defines
+            .iter()
+            .map(|(name, value)| (name.as_str(), value.as_str()))
+            .collect()
+    }
+}
+
+struct WatchedProgram {
+    files: ProgramFiles,
+    /// Every file the program was last built from, includes too, and when it was modified.
+    modified: Vec<(&'static str, Option<SystemTime>)>,
+}
+
+/// Modification times of the files behind each hot-reloaded program.
+#[derive(Default)]
+pub(crate) struct ShaderWatcher {
+    programs: HashMap<&'static str, WatchedProgram>,
+    last_poll: Option<Instant>,
+}
+
+impl ShaderWatcher {
+    /// Watches `sources`, the files `files` was last built from.
+    pub(crate) fn watch(
+        &mut self,
+        name: &'static str,
+        files: ProgramFiles,
+        sources: &[ShaderFile],
+    ) {
+        let modified = sources
+            .iter()
+            .map(|source| (source.path, modified(source.path)))
+            .collect();
+        self.programs
+            .insert(name, WatchedProgram { files, modified });
+    }
+
+    /// Programs with a file that changed since the previous poll, at most every
+    /// [`POLL_INTERVAL`].
+    pub(crate) fn poll(&mut self) -> Vec<&'static str> {
+        let now = Instant::now();
+        if self
+            .last_poll
+            .is_some_and(|last| now.duration_since(last) < POLL_INTERVAL)
+        {
+            return Vec::new();
+        }
+        self.last_poll = Some(now);
+
+        let mut changed = Vec::new();
+        for (name, program) in &mut self.programs {
+            let mut dirty = false;
+            for (path, last) in &mut program.modified {
+                // A file that is briefly missing while an editor saves it is not a change.
+                if let Some(time) = modified(*path)
+                    && Some(time) != *last
+                {
+                    *last = Some(time);
+                    dirty = true;
+                }
+            }
+            if dirty {
+                changed.push(*name);
+            }
+        }
+        changed
+    }
+
+    pub(crate) fn files(&self, name: &str) -> Option<&ProgramFiles> {
+        self.programs.get(name).map(|program| &program.files)
+    }
+}
+
+fn modified(path: impl AsRef<Path>) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
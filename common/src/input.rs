@@ -0,0 +1,206 @@
+use std::collections::HashSet;
+
+use glium::winit::{
+    event::{ElementState, KeyEvent, MouseButton, MouseScrollDelta, WindowEvent},
+    keyboard::{KeyCode, PhysicalKey},
+};
+
+/// Pixels per scroll line when a touchpad reports `MouseScrollDelta::PixelDelta`.
+const PIXELS_PER_LINE: f32 = 20.0;
+
+/// Keyboard and mouse state built from winit window events.
+///
+/// `MyWindow` feeds every event into [`InputState::handle_event`] and hands the state to
+/// `Drawable::update`. The `just_*` sets, mouse deltas and scroll cover everything since the
+/// previous frame that ran an update, and only the first update of a frame sees them; the
+/// ones after it catching up get them empty. `press`, `release`, `move_cursor` and friends
+/// inject synthetic input, since winit's `KeyEvent` cannot be constructed outside winit.
+#[derive(Default)]
+pub struct InputState {
+    keys: HashSet<KeyCode>,
+    keys_pressed: HashSet<KeyCode>,
+    keys_released: HashSet<KeyCode>,
+    buttons: HashSet<MouseButton>,
+    buttons_pressed: HashSet<MouseButton>,
+    buttons_released: HashSet<MouseButton>,
+    cursor: Option<(f64, f64)>,
+    mouse_delta: (f64, f64),
+    raw_motion: (f64, f64),
+    scroll: (f32, f32),
+}
+
+impl InputState {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        match event {
+            WindowEvent::KeyboardInput {
+                event:
+                    KeyEvent {
+                        physical_key: PhysicalKey::Code(code),
+                        state,
+                        ..
+                    },
+                ..
+            } => match state {
+                ElementState::Pressed => self.press(*code),
+                ElementState::Released => self.release(*code),
+            },
+            WindowEvent::MouseInput { state, button, .. } => match state {
+                ElementState::Pressed => self.press_button(*button),
+                ElementState::Released => self.release_button(*button),
+            },
+            WindowEvent::CursorMoved { position, .. } => self.move_cursor(position.x, position.y),
+            WindowEvent::CursorLeft { .. } => self.cursor = None,
+            WindowEvent::MouseWheel { delta, .. } => match delta {
+                MouseScrollDelta::LineDelta(x, y) => self.scroll_by(*x, *y),
+                MouseScrollDelta::PixelDelta(position) => self.scroll_by(
+                    position.x as f32 / PIXELS_PER_LINE,
+                    position.y as f32 / PIXELS_PER_LINE,
+                ),
+            },
+            // Keys released while unfocused never reach us, so drop everything held.
+            WindowEvent::Focused(false) => {
+                for code in std::mem::take(&mut self.keys) {
+                    self.keys_released.insert(code);
+                }
+                for button in std::mem::take(&mut self.buttons) {
+                    self.buttons_released.insert(button);
+                }
+            }
+            _ => {}
+        }
+    }
+
+    /// Clears the per-frame state, called after the first update of a frame.
+    pub fn end_frame(&mut self) {
+        self.keys_pressed.clear();
+        self.keys_released.clear();
+        self.buttons_pressed.clear();
+        self.buttons_released.clear();
+        self.mouse_delta = (0.0, 0.0);
+        self.raw_motion = (0.0, 0.0);
+        self.scroll = (0.0, 0.0);
+    }
+
+    pub fn press(&mut self, code: KeyCode) {
+        // Key repeat sends more `Pressed` events, only the first one counts as "just pressed".
+        if self.keys.insert(code) {
+            self.keys_pressed.insert(code);
+        }
+    }
+
+    pub fn release(&mut self, code: KeyCode) {
+        if self.keys.remove(&code) {
+            self.keys_released.insert(code);
+        }
+    }
+
+    pub fn press_button(&mut self, button: MouseButton) {
+        if self.buttons.insert(button) {
+            self.buttons_pressed.insert(button);
+        }
+    }
+
+    pub fn release_button(&mut self, button: MouseButton) {
+        if self.buttons.remove(&button) {
+            self.buttons_released.insert(button);
+        }
+    }
+
+    pub fn move_cursor(&mut self, x: f64, y: f64) {
+        if let Some((last_x, last_y)) = self.cursor {
+            self.mouse_delta.0 += x - last_x;
+            self.mouse_delta.1 += y - last_y;
+        }
+        self.cursor = Some((x, y));
+    }
+
+    /// Feeds `DeviceEvent::MouseMotion`, which keeps reporting while the cursor is grabbed.
+    pub fn add_raw_motion(&mut self, dx: f64, dy: f64) {
+        self.raw_motion.0 += dx;
+        self.raw_motion.1 += dy;
+    }
+
+    pub fn scroll_by(&mut self, x: f32, y: f32) {
+        self.scroll.0 += x;
+        self.scroll.1 += y;
+    }
+
+    pub fn is_pressed(&self, code: KeyCode) -> bool {
+        self.keys.contains(&code)
+    }
+
+    pub fn just_pressed(&self, code: KeyCode) -> bool {
+        self.keys_pressed.contains(&code)
+    }
+
+    pub fn just_released(&self, code: KeyCode) -> bool {
+        self.keys_released.contains(&code)
+    }
+
+    pub fn is_button_pressed(&self, button: MouseButton) -> bool {
+        self.buttons.contains(&button)
+    }
+
+    pub fn button_just_pressed(&self, button: MouseButton) -> bool {
+        self.buttons_pressed.contains(&button)
+    }
+
+    pub fn button_just_released(&self, button: MouseButton) -> bool {
+        self.buttons_released.contains(&button)
+    }
+
+    pub fn cursor_position(&self) -> Option<(f64, f64)> {
+        self.cursor
+    }
+
+    pub fn mouse_delta(&self) -> (f64, f64) {
+        self.mouse_delta
+    }
+
+    /// Unaccelerated device motion, unaffected by cursor clamping, warping or DPI scaling.
+    pub fn raw_mouse_delta(&self) -> (f64, f64) {
+        self.raw_motion
+    }
+
+    /// Scroll in lines, positive `y` away from the user.
+    pub fn scroll(&self) -> (f32, f32) {
+        self.scroll
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn end_frame_keeps_held_state() {
+        let mut input = InputState::new();
+        input.press(KeyCode::KeyW);
+        input.press(KeyCode::KeyW);
+        input.press_button(MouseButton::Left);
+        input.move_cursor(10.0, 10.0);
+        input.move_cursor(12.0, 7.0);
+        input.add_raw_motion(1.5, 0.5);
+        assert!(input.just_pressed(KeyCode::KeyW));
+        assert_eq!(input.mouse_delta(), (2.0, -3.0));
+        assert_eq!(input.raw_mouse_delta(), (1.5, 0.5));
+
+        input.end_frame();
+        assert!(input.is_pressed(KeyCode::KeyW));
+        assert!(!input.just_pressed(KeyCode::KeyW));
+        assert!(input.is_button_pressed(MouseButton::Left));
+        assert_eq!(input.cursor_position(), Some((12.0, 7.0)));
+        assert_eq!(input.mouse_delta(), (0.0, 0.0));
+        assert_eq!(input.raw_mouse_delta(), (0.0, 0.0));
+
+        // Key repeat while held is not a new press.
+        input.press(KeyCode::KeyW);
+        assert!(!input.just_pressed(KeyCode::KeyW));
+        input.release(KeyCode::KeyW);
+        assert!(input.just_released(KeyCode::KeyW));
+    }
+}
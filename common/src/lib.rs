@@ -0,0 +1,47 @@
+//! Code shared by the lessons: mesh, projection, render state, lighting, shader preprocessing
+//! and texture loading for the texture, transform and animation crates, the window and
+//! offscreen hosts that run the transform and animation scenes, and headless rendering with
+//! golden-image checks for the scene tests.
+
+mod capture;
+mod clock;
+mod display;
+pub mod ecs;
+mod glsl;
+pub mod golden;
+pub mod headless;
+mod hot_reload;
+mod input;
+pub mod lighting;
+mod material;
+mod mesh;
+mod offscreen;
+pub mod primitives;
+mod projection;
+mod render_state;
+mod resources;
+mod sampler;
+mod texture_loader;
+mod timestep;
+mod transform;
+pub mod vector;
+mod window;
+
+pub use capture::Capture;
+pub use clock::{Clock, FixedClock, RealClock, ScaledClock};
+pub use display::{create_display, linear_program};
+pub use glsl::{
+    PreprocessError, Preprocessed, Preprocessor, ShaderError, ShaderFile, compile_program,
+};
+pub use input::InputState;
+pub use material::{Material, MaterialUniforms, MaterialValue};
+pub use mesh::{Mesh, MeshVertex};
+pub use offscreen::Offscreen;
+pub use projection::{Projection, ProjectionKind};
+pub use render_state::{DepthConvention, RenderState, RenderStateError};
+pub use resources::Resources;
+pub use sampler::{SamplerDescriptor, TextureSampler, mip_levels};
+pub use texture_loader::{ColorSpace, Texture, TextureError, TextureLoader, missing_image};
+pub use timestep::Timestep;
+pub use transform::Transform;
+pub use window::{Drawable, FrameContext, MyWindow, run_updates};
\ No newline at end of file
@@ -0,0 +1,321 @@
+//! Directional, point and spot lights for the Blinn-Phong shaders in `shaders/lit.*`.
+//!
+//! Lights are given in world space. [`Lighting`] implements `Uniforms` and is combined with a
+//! draw's own uniforms by [`Lighting::with`]. The shaders also expect `shininess`, the
+//! specular exponent of the surface:
+//!
+//! ```ignore
+//! let uniforms = lighting.with(glium::uniform! {
+//!     transform: mvp,
+//!     model: model,
+//!     view: view,
+//!     shininess: 32.0f32,
+//! });
+//! ```
+
+use glium::uniforms::{UniformValue, Uniforms};
+
+/// Lights of each kind beyond this many are ignored. The shaders size their light arrays with
+/// `MAX_LIGHTS`, which the default [`Preprocessor`](crate::Preprocessor) defines to this value.
+pub const MAX_LIGHTS: usize = 4;
+
+/// Light from infinitely far away, like the sun.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct DirectionalLight {
+    /// Direction the light travels in, need not be normalized.
+    pub direction: [f32; 3],
+    pub color: [f32; 3],
+    pub intensity: f32,
+}
+
+impl DirectionalLight {
+    pub fn new(direction: [f32; 3]) -> Self {
+        Self {
+            direction,
+            color: [1.0, 1.0, 1.0],
+            intensity: 1.0,
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 3], intensity: f32) -> Self {
+        self.color = color;
+        self.intensity = intensity;
+        self
+    }
+}
+
+/// Light emitted in all directions from a point, fading out to nothing at `range`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct PointLight {
+    pub position: [f32; 3],
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub range: f32,
+}
+
+impl PointLight {
+    pub fn new(position: [f32; 3], range: f32) -> Self {
+        Self {
+            position,
+            color: [1.0, 1.0, 1.0],
+            intensity: 1.0,
+            range,
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 3], intensity: f32) -> Self {
+        self.color = color;
+        self.intensity = intensity;
+        self
+    }
+}
+
+/// A point light restricted to a cone, with a soft edge between the two angles.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SpotLight {
+    pub position: [f32; 3],
+    pub direction: [f32; 3],
+    pub color: [f32; 3],
+    pub intensity: f32,
+    pub range: f32,
+    /// Half-angle in degrees inside which the light is at full strength.
+    pub inner_angle: f32,
+    /// Half-angle in degrees outside which there is no light.
+    pub outer_angle: f32,
+}
+
+impl SpotLight {
+    pub fn new(position: [f32; 3], direction: [f32; 3], range: f32) -> Self {
+        Self {
+            position,
+            direction,
+            color: [1.0, 1.0, 1.0],
+            intensity: 1.0,
+            range,
+            inner_angle: 15.0,
+            outer_angle: 25.0,
+        }
+    }
+
+    pub fn with_color(mut self, color: [f32; 3], intensity: f32) -> Self {
+        self.color = color;
+        self.intensity = intensity;
+        self
+    }
+
+    pub fn with_angles(mut self, inner: f32, outer: f32) -> Self {
+        self.inner_angle = inner.min(outer);
+        self.outer_angle = outer;
+        self
+    }
+}
+
+/// The lights of a scene.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Lighting {
+    pub ambient: [f32; 3],
+    pub directional: Vec<DirectionalLight>,
+    pub points: Vec<PointLight>,
+    pub spots: Vec<SpotLight>,
+}
+
+impl Default for Lighting {
+    /// Dim ambient light and a white light from the upper left front.
+    fn default() -> Self {
+        Self {
+            ambient: [0.1, 0.1, 0.1],
+            directional: vec![DirectionalLight::new([1.0, -1.0, -1.0])],
+            points: Vec::new(),
+            spots: Vec::new(),
+        }
+    }
+}
+
+impl Lighting {
+    /// No lights at all, only `ambient`.
+    pub fn new(ambient: [f32; 3]) -> Self {
+        Self {
+            ambient,
+            directional: Vec::new(),
+            points: Vec::new(),
+            spots: Vec::new(),
+        }
+    }
+
+    /// Adds the lights to the draw's own uniforms.
+    pub fn with<U: Uniforms>(&self, uniforms: U) -> LitUniforms<'_, U> {
+        LitUniforms {
+            lighting: self,
+            uniforms,
+        }
+    }
+}
+
+fn scaled(color: [f32; 3], intensity: f32) -> UniformValue<'static> {
+    UniformValue::Vec3(color.map(|c| c * intensity))
+}
+
+impl Uniforms for Lighting {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        output("ambient", UniformValue::Vec3(self.ambient));
+
+        let directional = &self.directional[..self.directional.len().min(MAX_LIGHTS)];
+        output(
+            "directional_count",
+            UniformValue::SignedInt(directional.len() as i32),
+        );
+        for (i, light) in directional.iter().enumerate() {
+            let field = |name: &str| format!("directional_lights[{i}].{name}");
+            output(&field("direction"), UniformValue::Vec3(light.direction));
+            output(&field("color"), scaled(light.color, light.intensity));
+        }
+
+        let points = &self.points[..self.points.len().min(MAX_LIGHTS)];
+        output("point_count", UniformValue::SignedInt(points.len() as i32));
+        for (i, light) in points.iter().enumerate() {
+            let field = |name: &str| format!("point_lights[{i}].{name}");
+            output(&field("position"), UniformValue::Vec3(light.position));
+            output(&field("color"), scaled(light.color, light.intensity));
+            output(&field("range"), UniformValue::Float(light.range));
+        }
+
+        let spots = &self.spots[..self.spots.len().min(MAX_LIGHTS)];
+        output("spot_count", UniformValue::SignedInt(spots.len() as i32));
+        for (i, light) in spots.iter().enumerate() {
+            let field = |name: &str| format!("spot_lights[{i}].{name}");
+            output(&field("position"), UniformValue::Vec3(light.position));
+            output(&field("direction"), UniformValue::Vec3(light.direction));
+            output(&field("color"), scaled(light.color, light.intensity));
+            output(&field("range"), UniformValue::Float(light.range));
+            output(
+                &field("inner_cos"),
+                UniformValue::Float(light.inner_angle.to_radians().cos()),
+            );
+            output(
+                &field("outer_cos"),
+                UniformValue::Float(light.outer_angle.to_radians().cos()),
+            );
+        }
+    }
+}
+
+/// A draw's uniforms together with a [`Lighting`], see [`Lighting::with`].
+pub struct LitUniforms<'l, U> {
+    lighting: &'l Lighting,
+    uniforms: U,
+}
+
+impl<U: Uniforms> Uniforms for LitUniforms<'_, U> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        self.uniforms.visit_values(&mut output);
+        self.lighting.visit_values(output);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(uniforms: &impl Uniforms) -> Vec<String> {
+        let mut names = Vec::new();
+        uniforms.visit_values(|name, _| names.push(name.to_owned()));
+        names
+    }
+
+    fn value<'a>(uniforms: &'a impl Uniforms, name: &str) -> UniformValue<'a> {
+        let mut found = None;
+        uniforms.visit_values(|n, value| {
+            if n == name {
+                found = Some(value);
+            }
+        });
+        found.unwrap_or_else(|| panic!("no uniform `{name}`"))
+    }
+
+    fn count(uniforms: &impl Uniforms, name: &str) -> i32 {
+        match value(uniforms, name) {
+            UniformValue::SignedInt(count) => count,
+            _ => panic!("`{name}` is not an int"),
+        }
+    }
+
+    fn vec3(uniforms: &impl Uniforms, name: &str) -> [f32; 3] {
+        match value(uniforms, name) {
+            UniformValue::Vec3(value) => value,
+            _ => panic!("`{name}` is not a vec3"),
+        }
+    }
+
+    #[test]
+    fn lights_beyond_max_lights_are_dropped() {
+        let mut lighting = Lighting::new([0.0; 3]);
+        lighting.directional = vec![DirectionalLight::new([0.0, -1.0, 0.0]); MAX_LIGHTS + 2];
+        lighting.points = vec![PointLight::new([0.0; 3], 1.0); MAX_LIGHTS + 1];
+        lighting.spots = vec![SpotLight::new([0.0; 3], [0.0, 0.0, -1.0], 1.0); 2];
+
+        assert_eq!(count(&lighting, "directional_count"), MAX_LIGHTS as i32);
+        assert_eq!(count(&lighting, "point_count"), MAX_LIGHTS as i32);
+        assert_eq!(count(&lighting, "spot_count"), 2);
+        let names = names(&lighting);
+        let last = MAX_LIGHTS - 1;
+        assert!(names.contains(&format!("directional_lights[{last}].direction")));
+        assert!(!names.contains(&format!("directional_lights[{MAX_LIGHTS}].direction")));
+        assert!(!names.contains(&format!("point_lights[{MAX_LIGHTS}].position")));
+    }
+
+    #[test]
+    fn uniform_names_match_the_shader_structs() {
+        let mut lighting = Lighting::new([0.2; 3]);
+        lighting
+            .directional
+            .push(DirectionalLight::new([0.0, -1.0, 0.0]));
+        lighting.points.push(PointLight::new([1.0, 2.0, 3.0], 5.0));
+        lighting
+            .spots
+            .push(SpotLight::new([0.0; 3], [0.0, 0.0, -1.0], 5.0));
+        assert_eq!(
+            names(&lighting),
+            [
+                "ambient",
+                "directional_count",
+                "directional_lights[0].direction",
+                "directional_lights[0].color",
+                "point_count",
+                "point_lights[0].position",
+                "point_lights[0].color",
+                "point_lights[0].range",
+                "spot_count",
+                "spot_lights[0].position",
+                "spot_lights[0].direction",
+                "spot_lights[0].color",
+                "spot_lights[0].range",
+                "spot_lights[0].inner_cos",
+                "spot_lights[0].outer_cos",
+            ]
+        );
+        assert_eq!(vec3(&lighting, "point_lights[0].position"), [1.0, 2.0, 3.0]);
+    }
+
+    #[test]
+    fn intensity_is_folded_into_the_color() {
+        let mut lighting = Lighting::new([0.0; 3]);
+        lighting
+            .directional
+            .push(DirectionalLight::new([0.0, -1.0, 0.0]).with_color([1.0, 0.5, 0.0], 2.0));
+        lighting
+            .points
+            .push(PointLight::new([0.0; 3], 1.0).with_color([0.5, 0.5, 1.0], 0.5));
+        assert_eq!(
+            vec3(&lighting, "directional_lights[0].color"),
+            [2.0, 1.0, 0.0]
+        );
+        assert_eq!(vec3(&lighting, "point_lights[0].color"), [0.25, 0.25, 0.5]);
+    }
+
+    #[test]
+    fn with_passes_the_draw_uniforms_first() {
+        let lighting = Lighting::new([0.0; 3]);
+        let uniforms = lighting.with(glium::uniform! { shininess: 32.0f32 });
+        assert_eq!(names(&uniforms)[..2], ["shininess", "ambient"]);
+    }
+}
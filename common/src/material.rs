@@ -0,0 +1,328 @@
+use glium::{
+    DrawError, Surface,
+    index::IndicesSource,
+    uniforms::{UniformValue, Uniforms},
+    vertex::MultiVerticesSource,
+};
+
+use crate::{DepthConvention, RenderState, Resources, SamplerDescriptor};
+
+/// A scalar or vector material parameter.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum MaterialValue {
+    Float(f32),
+    Int(i32),
+    Vec2([f32; 2]),
+    Vec3([f32; 3]),
+    Vec4([f32; 4]),
+}
+
+impl MaterialValue {
+    fn uniform_value(self) -> UniformValue<'static> {
+        match self {
+            Self::Float(value) => UniformValue::Float(value),
+            Self::Int(value) => UniformValue::SignedInt(value),
+            Self::Vec2(value) => UniformValue::Vec2(value),
+            Self::Vec3(value) => UniformValue::Vec3(value),
+            Self::Vec4(value) => UniformValue::Vec4(value),
+        }
+    }
+}
+
+impl From<f32> for MaterialValue {
+    fn from(value: f32) -> Self {
+        Self::Float(value)
+    }
+}
+
+impl From<i32> for MaterialValue {
+    fn from(value: i32) -> Self {
+        Self::Int(value)
+    }
+}
+
+impl From<[f32; 2]> for MaterialValue {
+    fn from(value: [f32; 2]) -> Self {
+        Self::Vec2(value)
+    }
+}
+
+impl From<[f32; 3]> for MaterialValue {
+    fn from(value: [f32; 3]) -> Self {
+        Self::Vec3(value)
+    }
+}
+
+impl From<[f32; 4]> for MaterialValue {
+    fn from(value: [f32; 4]) -> Self {
+        Self::Vec4(value)
+    }
+}
+
+#[derive(Clone, Debug)]
+struct TextureSlot {
+    uniform: &'static str,
+    texture: &'static str,
+    /// Overrides the sampler configured for the texture in [`Resources`].
+    sampler: Option<SamplerDescriptor>,
+}
+
+/// How a mesh looks: the program it is drawn with, the textures and parameters bound to the
+/// program's uniforms, and the render state.
+///
+/// Programs and textures are names in [`Resources`]. A draw only supplies its per-object
+/// uniforms such as `transform`, [`Material::draw`] adds the rest:
+///
+/// ```ignore
+/// let stone = Material::new("lit", projection.depth_convention())
+///     .with_texture("tex", "stone")
+///     .with("shininess", 32.0);
+/// stone.draw(target, resources, vertices, indices, glium::uniform! { transform: mvp })?;
+/// ```
+#[derive(Clone, Debug)]
+pub struct Material {
+    pub program: &'static str,
+    pub render_state: RenderState,
+    textures: Vec<TextureSlot>,
+    parameters: Vec<(&'static str, MaterialValue)>,
+}
+
+impl Material {
+    /// No textures or parameters, drawn [`RenderState::opaque`] for the depth range of the
+    /// projection the material is used with.
+    pub fn new(program: &'static str, depth: DepthConvention) -> Self {
+        Self {
+            program,
+            render_state: RenderState::opaque(depth),
+            textures: Vec::new(),
+            parameters: Vec::new(),
+        }
+    }
+
+    pub fn with_render_state(mut self, render_state: RenderState) -> Self {
+        self.render_state = render_state;
+        self
+    }
+
+    /// Binds the texture `texture` from [`Resources`] to the sampler uniform `uniform`.
+    pub fn with_texture(mut self, uniform: &'static str, texture: &'static str) -> Self {
+        self.set_texture(uniform, texture, None);
+        self
+    }
+
+    /// Like [`with_texture`](Self::with_texture), sampled with `sampler` instead of the
+    /// settings the texture has in [`Resources`].
+    pub fn with_sampled_texture(
+        mut self,
+        uniform: &'static str,
+        texture: &'static str,
+        sampler: SamplerDescriptor,
+    ) -> Self {
+        self.set_texture(uniform, texture, Some(sampler));
+        self
+    }
+
+    pub fn with(mut self, name: &'static str, value: impl Into<MaterialValue>) -> Self {
+        self.set(name, value);
+        self
+    }
+
+    pub fn set_texture(
+        &mut self,
+        uniform: &'static str,
+        texture: &'static str,
+        sampler: Option<SamplerDescriptor>,
+    ) {
+        let slot = TextureSlot {
+            uniform,
+            texture,
+            sampler,
+        };
+        match self.textures.iter_mut().find(|s| s.uniform == uniform) {
+            Some(existing) => *existing = slot,
+            None => self.textures.push(slot),
+        }
+    }
+
+    pub fn set(&mut self, name: &'static str, value: impl Into<MaterialValue>) {
+        let value = value.into();
+        match self.parameters.iter_mut().find(|(n, _)| *n == name) {
+            Some((_, existing)) => *existing = value,
+            None => self.parameters.push((name, value)),
+        }
+    }
+
+    pub fn get(&self, name: &str) -> Option<MaterialValue> {
+        self.parameters
+            .iter()
+            .find(|(n, _)| *n == name)
+            .map(|(_, value)| *value)
+    }
+
+    /// Name of the texture bound to `uniform`.
+    pub fn texture(&self, uniform: &str) -> Option<&'static str> {
+        self.textures
+            .iter()
+            .find(|slot| slot.uniform == uniform)
+            .map(|slot| slot.texture)
+    }
+
+    /// `uniforms` followed by the material's parameters and textures.
+    pub fn uniforms<'a, U: Uniforms>(
+        &'a self,
+        resources: &'a Resources,
+        uniforms: U,
+    ) -> MaterialUniforms<'a, U> {
+        MaterialUniforms {
+            material: self,
+            resources,
+            uniforms,
+        }
+    }
+
+    /// Draws with the material's program, uniforms and render state.
+    pub fn draw<'a, 'b, S, V, I, U>(
+        &self,
+        target: &mut S,
+        resources: &Resources,
+        vertices: V,
+        indices: I,
+        uniforms: U,
+    ) -> Result<(), DrawError>
+    where
+        S: Surface,
+        V: MultiVerticesSource<'b>,
+        I: Into<IndicesSource<'a>>,
+        U: Uniforms,
+    {
+        target.draw(
+            vertices,
+            indices,
+            resources.program(self.program),
+            &self.uniforms(resources, uniforms),
+            &self.render_state.draw_parameters(),
+        )
+    }
+}
+
+/// A draw's uniforms together with a [`Material`], see [`Material::uniforms`].
+pub struct MaterialUniforms<'a, U> {
+    material: &'a Material,
+    resources: &'a Resources,
+    uniforms: U,
+}
+
+impl<U: Uniforms> Uniforms for MaterialUniforms<'_, U> {
+    fn visit_values<'a, F: FnMut(&str, UniformValue<'a>)>(&'a self, mut output: F) {
+        self.uniforms.visit_values(&mut output);
+        for (name, value) in &self.material.parameters {
+            output(name, value.uniform_value());
+        }
+        for slot in &self.material.textures {
+            let sampler = match slot.sampler {
+                Some(sampler) => sampler.sampler(self.resources.texture(slot.texture)),
+                None => self.resources.sampler(slot.texture),
+            };
+            output(slot.uniform, sampler.uniform_value());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glium::{
+        DepthTest, Texture2d,
+        uniforms::{EmptyUniforms, MagnifySamplerFilter, SamplerBehavior},
+    };
+
+    use super::*;
+    use crate::Texture;
+
+    /// Resources with two 1x1 textures, `stone` sampled nearest.
+    fn resources() -> Resources {
+        let context = crate::headless::context().unwrap();
+        let mut resources = Resources::new();
+        for name in ["stone", "moss"] {
+            resources.insert_texture(name, Texture2d::empty(&context, 1, 1).unwrap());
+        }
+        resources.set_sampler("stone", SamplerDescriptor::nearest());
+        resources
+    }
+
+    fn visit<U: Uniforms>(uniforms: &U) -> Vec<(String, UniformValue<'_>)> {
+        let mut values = Vec::new();
+        uniforms.visit_values(|name, value| values.push((name.to_owned(), value)));
+        values
+    }
+
+    fn texture_sampler<'a>(
+        values: &[(String, UniformValue<'a>)],
+        uniform: &str,
+    ) -> (&'a Texture2d, SamplerBehavior) {
+        match values.iter().find(|(name, _)| name == uniform) {
+            Some((_, UniformValue::Texture2d(texture, Some(behavior)))) => (texture, *behavior),
+            _ => panic!("no sampled texture bound to `{uniform}`"),
+        }
+    }
+
+    fn linear(texture: &Texture) -> &Texture2d {
+        match texture {
+            Texture::Linear(texture) => texture,
+            Texture::Srgb(_) => unreachable!("the test textures are linear"),
+        }
+    }
+
+    #[test]
+    fn uniforms_bind_every_parameter_and_texture_by_name() {
+        let resources = resources();
+        let material = Material::new("lit", DepthConvention::Standard)
+            .with("shininess", 32.0)
+            .with("tint", [1.0, 0.5, 0.0])
+            .with("layers", 2)
+            .with_texture("diffuse", "stone")
+            .with_texture("detail", "moss");
+        let uniforms = material.uniforms(&resources, glium::uniform! { alpha: 0.5f32 });
+        let values = visit(&uniforms);
+        let names: Vec<_> = values.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(
+            names,
+            ["alpha", "shininess", "tint", "layers", "diffuse", "detail"]
+        );
+        assert!(matches!(values[1].1, UniformValue::Float(32.0)));
+        assert!(matches!(values[2].1, UniformValue::Vec3([1.0, 0.5, 0.0])));
+        assert!(matches!(values[3].1, UniformValue::SignedInt(2)));
+        let (diffuse, _) = texture_sampler(&values, "diffuse");
+        let (detail, _) = texture_sampler(&values, "detail");
+        assert!(std::ptr::eq(diffuse, linear(resources.texture("stone"))));
+        assert!(std::ptr::eq(detail, linear(resources.texture("moss"))));
+    }
+
+    #[test]
+    fn material_sampler_overrides_the_resources_one() {
+        let resources = resources();
+        let material = Material::new("lit", DepthConvention::Standard)
+            .with_texture("diffuse", "stone")
+            .with_sampled_texture("smooth", "stone", SamplerDescriptor::default());
+        let uniforms = material.uniforms(&resources, EmptyUniforms);
+        let values = visit(&uniforms);
+        let (_, configured) = texture_sampler(&values, "diffuse");
+        assert_eq!(configured.magnify_filter, MagnifySamplerFilter::Nearest);
+        let (_, overridden) = texture_sampler(&values, "smooth");
+        assert_eq!(overridden.magnify_filter, MagnifySamplerFilter::Linear);
+    }
+
+    #[test]
+    fn new_takes_the_depth_test_from_the_convention() {
+        let standard = Material::new("lit", DepthConvention::Standard);
+        assert_eq!(
+            standard.render_state.draw_parameters().depth.test,
+            DepthTest::IfLess
+        );
+        let reversed = Material::new("lit", DepthConvention::Reversed);
+        assert_eq!(
+            reversed.render_state.draw_parameters().depth.test,
+            DepthTest::IfMore
+        );
+        assert_eq!(reversed.render_state.clear_depth(), Some(0.0));
+    }
+}
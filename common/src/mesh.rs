@@ -0,0 +1,392 @@
+use std::collections::HashMap;
+
+use glium::{
+    IndexBuffer, VertexBuffer,
+    backend::Facade,
+    implement_vertex,
+    index::{self, PrimitiveType},
+    vertex,
+};
+
+use crate::vector::{add, cross, dot, length, normalize, scale, sub};
+
+/// Interleaved layout used by the shared shaders: `position`, `normal`, `tangent`, `tex_coord`,
+/// `color`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct MeshVertex {
+    pub position: [f32; 3],
+    pub normal: [f32; 3],
+    /// `w` is the handedness of the bitangent, `cross(normal, tangent.xyz) * w`.
+    pub tangent: [f32; 4],
+    pub tex_coord: [f32; 2],
+    pub color: [f32; 4],
+}
+
+implement_vertex!(MeshVertex, position, normal, tangent, tex_coord, color);
+
+impl MeshVertex {
+    pub const fn new(position: [f32; 3]) -> Self {
+        Self {
+            position,
+            normal: [0.0, 0.0, 0.0],
+            tangent: [1.0, 0.0, 0.0, 1.0],
+            tex_coord: [0.0, 0.0],
+            color: [1.0, 1.0, 1.0, 1.0],
+        }
+    }
+
+    pub const fn with_normal(mut self, normal: [f32; 3]) -> Self {
+        self.normal = normal;
+        self
+    }
+
+    pub const fn with_tangent(mut self, tangent: [f32; 4]) -> Self {
+        self.tangent = tangent;
+        self
+    }
+
+    pub const fn with_tex_coord(mut self, tex_coord: [f32; 2]) -> Self {
+        self.tex_coord = tex_coord;
+        self
+    }
+
+    pub const fn with_color(mut self, color: [f32; 4]) -> Self {
+        self.color = color;
+        self
+    }
+
+    /// Bit pattern used to find identical vertices, `0.0` and `-0.0` are kept apart.
+    fn key(&self) -> [u32; 16] {
+        let mut key = [0; 16];
+        let values = self
+            .position
+            .iter()
+            .chain(&self.normal)
+            .chain(&self.tangent)
+            .chain(&self.tex_coord)
+            .chain(&self.color);
+        for (slot, value) in key.iter_mut().zip(values) {
+            *slot = value.to_bits();
+        }
+        key
+    }
+}
+
+/// Indexed triangle list with optional per-vertex attributes.
+///
+/// `normals`, `tangents`, `tex_coords` and `colors` are either empty or as long as `positions`.
+#[derive(Clone, Debug, Default)]
+pub struct Mesh {
+    pub positions: Vec<[f32; 3]>,
+    pub normals: Vec<[f32; 3]>,
+    pub tangents: Vec<[f32; 4]>,
+    pub tex_coords: Vec<[f32; 2]>,
+    pub colors: Vec<[f32; 4]>,
+    pub indices: Vec<u32>,
+}
+
+impl Mesh {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Builds an indexed mesh from a flat triangle list, sharing identical vertices.
+    pub fn from_triangles(vertices: impl IntoIterator<Item = MeshVertex>) -> Self {
+        let mut mesh = Self::new();
+        let mut seen = HashMap::new();
+        for vertex in vertices {
+            let index = *seen
+                .entry(vertex.key())
+                .or_insert_with(|| mesh.push_vertex(vertex));
+            mesh.indices.push(index);
+        }
+        mesh
+    }
+
+    pub fn vertex_count(&self) -> usize {
+        self.positions.len()
+    }
+
+    pub fn triangle_count(&self) -> usize {
+        self.indices.len() / 3
+    }
+
+    /// Appends a vertex without deduplication and returns its index.
+    pub fn push_vertex(&mut self, vertex: MeshVertex) -> u32 {
+        let index = self.positions.len() as u32;
+        self.positions.push(vertex.position);
+        self.normals.push(vertex.normal);
+        self.tangents.push(vertex.tangent);
+        self.tex_coords.push(vertex.tex_coord);
+        self.colors.push(vertex.color);
+        index
+    }
+
+    pub fn push_triangle(&mut self, a: u32, b: u32, c: u32) {
+        self.indices.extend([a, b, c]);
+    }
+
+    /// Appends the vertices and triangles of `other`.
+    pub fn append(&mut self, other: &Mesh) {
+        let offset = self.vertex_count() as u32;
+        for i in 0..other.vertex_count() {
+            self.push_vertex(other.vertex(i));
+        }
+        self.indices
+            .extend(other.indices.iter().map(|i| i + offset));
+    }
+
+    /// Vertex `index` with missing attributes filled by the [`MeshVertex::new`] defaults.
+    pub fn vertex(&self, index: usize) -> MeshVertex {
+        let mut vertex = MeshVertex::new(self.positions[index]);
+        if let Some(normal) = self.normals.get(index) {
+            vertex.normal = *normal;
+        }
+        if let Some(tangent) = self.tangents.get(index) {
+            vertex.tangent = *tangent;
+        }
+        if let Some(tex_coord) = self.tex_coords.get(index) {
+            vertex.tex_coord = *tex_coord;
+        }
+        if let Some(color) = self.colors.get(index) {
+            vertex.color = *color;
+        }
+        vertex
+    }
+
+    pub fn vertices(&self) -> Vec<MeshVertex> {
+        (0..self.vertex_count()).map(|i| self.vertex(i)).collect()
+    }
+
+    /// Merges identical vertices and drops the ones no index refers to.
+    pub fn deduplicate(&mut self) {
+        let vertices = self.vertices();
+        let indices = std::mem::take(&mut self.indices);
+        *self = Self::from_triangles(indices.into_iter().map(|i| vertices[i as usize]));
+    }
+
+    /// Replaces the normals with area-weighted averages of the adjacent face normals.
+    pub fn compute_normals(&mut self) {
+        let mut normals = vec![[0.0; 3]; self.vertex_count()];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let normal = cross(
+                sub(self.positions[b], self.positions[a]),
+                sub(self.positions[c], self.positions[a]),
+            );
+            for i in [a, b, c] {
+                normals[i] = add(normals[i], normal);
+            }
+        }
+        self.normals = normals
+            .into_iter()
+            .map(|normal| {
+                if length(normal) > 0.0 {
+                    normalize(normal)
+                } else {
+                    [0.0, 1.0, 0.0]
+                }
+            })
+            .collect();
+    }
+
+    /// Derives per-vertex tangents from the texture coordinates, orthogonalized against the
+    /// normals. Does nothing unless the mesh has both.
+    pub fn compute_tangents(&mut self) {
+        let count = self.vertex_count();
+        if self.normals.len() != count || self.tex_coords.len() != count {
+            return;
+        }
+
+        let mut tangents = vec![[0.0; 3]; count];
+        let mut bitangents = vec![[0.0; 3]; count];
+        for triangle in self.indices.chunks_exact(3) {
+            let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+            let e1 = sub(self.positions[b], self.positions[a]);
+            let e2 = sub(self.positions[c], self.positions[a]);
+            let (du1, dv1) = (
+                self.tex_coords[b][0] - self.tex_coords[a][0],
+                self.tex_coords[b][1] - self.tex_coords[a][1],
+            );
+            let (du2, dv2) = (
+                self.tex_coords[c][0] - self.tex_coords[a][0],
+                self.tex_coords[c][1] - self.tex_coords[a][1],
+            );
+            let det = du1 * dv2 - du2 * dv1;
+            if det.abs() < f32::EPSILON {
+                continue;
+            }
+            let r = 1.0 / det;
+            let tangent = scale(sub(scale(e1, dv2), scale(e2, dv1)), r);
+            let bitangent = scale(sub(scale(e2, du1), scale(e1, du2)), r);
+            for i in [a, b, c] {
+                tangents[i] = add(tangents[i], tangent);
+                bitangents[i] = add(bitangents[i], bitangent);
+            }
+        }
+
+        self.tangents = (0..count)
+            .map(|i| {
+                let normal = self.normals[i];
+                let tangent = sub(tangents[i], scale(normal, dot(normal, tangents[i])));
+                let tangent = if length(tangent) > 1e-6 {
+                    normalize(tangent)
+                } else {
+                    // No usable UV gradient here, e.g. at a sphere pole.
+                    any_orthogonal(normal)
+                };
+                let w = if dot(cross(normal, tangent), bitangents[i]) < 0.0 {
+                    -1.0
+                } else {
+                    1.0
+                };
+                [tangent[0], tangent[1], tangent[2], w]
+            })
+            .collect();
+    }
+
+    /// Uploads the vertices converted into any layout that can be built from [`MeshVertex`].
+    pub fn vertex_buffer<F, V>(
+        &self,
+        facade: &F,
+    ) -> Result<VertexBuffer<V>, vertex::BufferCreationError>
+    where
+        F: Facade,
+        V: vertex::Vertex + From<MeshVertex> + Send + 'static,
+    {
+        let data = (0..self.vertex_count())
+            .map(|i| V::from(self.vertex(i)))
+            .collect::<Vec<_>>();
+        VertexBuffer::new(facade, &data)
+    }
+
+    pub fn index_buffer<F: Facade>(
+        &self,
+        facade: &F,
+    ) -> Result<IndexBuffer<u32>, index::BufferCreationError> {
+        IndexBuffer::new(facade, PrimitiveType::TrianglesList, &self.indices)
+    }
+}
+
+fn any_orthogonal(normal: [f32; 3]) -> [f32; 3] {
+    let axis = if normal[0].abs() < 0.9 {
+        [1.0, 0.0, 0.0]
+    } else {
+        [0.0, 1.0, 0.0]
+    };
+    normalize(cross(axis, normal))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A unit quad in the XY plane facing +Z, as two counter-clockwise triangles with the usual
+    /// texture coordinates.
+    fn quad() -> Mesh {
+        let corner = |x: f32, y: f32| MeshVertex::new([x, y, 0.0]).with_tex_coord([x, y]);
+        let [a, b, c, d] = [
+            corner(0.0, 0.0),
+            corner(1.0, 0.0),
+            corner(1.0, 1.0),
+            corner(0.0, 1.0),
+        ];
+        Mesh::from_triangles([a, b, c, a, c, d])
+    }
+
+    #[test]
+    fn from_triangles_shares_identical_vertices() {
+        let mesh = quad();
+        assert_eq!(mesh.vertex_count(), 4);
+        assert_eq!(mesh.triangle_count(), 2);
+        assert_eq!(mesh.indices, [0, 1, 2, 0, 2, 3]);
+    }
+
+    #[test]
+    fn keys_compare_bits() {
+        let zero = MeshVertex::new([0.0, 0.0, 0.0]);
+        let negative_zero = MeshVertex::new([-0.0, 0.0, 0.0]);
+        let mesh = Mesh::from_triangles([zero, negative_zero, zero]);
+        assert_eq!(mesh.vertex_count(), 2);
+        assert_eq!(mesh.indices, [0, 1, 0]);
+
+        let nan = MeshVertex::new([f32::NAN, 0.0, 0.0]);
+        let mesh = Mesh::from_triangles([nan, nan, zero]);
+        assert_eq!(mesh.vertex_count(), 2);
+        assert_eq!(mesh.indices, [0, 0, 1]);
+    }
+
+    #[test]
+    fn vertex_fills_missing_attributes_with_defaults() {
+        let mesh = Mesh {
+            positions: vec![[1.0, 2.0, 3.0]],
+            tex_coords: vec![[0.5, 0.25]],
+            ..Mesh::new()
+        };
+        assert_eq!(
+            mesh.vertex(0),
+            MeshVertex::new([1.0, 2.0, 3.0]).with_tex_coord([0.5, 0.25])
+        );
+        let vertex = mesh.vertex(0);
+        assert_eq!(vertex.normal, [0.0, 0.0, 0.0]);
+        assert_eq!(vertex.tangent, [1.0, 0.0, 0.0, 1.0]);
+        assert_eq!(vertex.color, [1.0, 1.0, 1.0, 1.0]);
+    }
+
+    #[test]
+    fn deduplicate_merges_and_drops_unused_vertices() {
+        let mut mesh = Mesh::new();
+        for position in [
+            [0.0, 0.0, 0.0],
+            [1.0, 0.0, 0.0],
+            [0.0, 0.0, 0.0],
+            [5.0, 5.0, 5.0],
+        ] {
+            mesh.push_vertex(MeshVertex::new(position));
+        }
+        mesh.push_triangle(0, 1, 2);
+        mesh.deduplicate();
+        assert_eq!(mesh.positions, [[0.0, 0.0, 0.0], [1.0, 0.0, 0.0]]);
+        assert_eq!(mesh.indices, [0, 1, 0]);
+    }
+
+    #[test]
+    fn compute_normals_follows_the_winding() {
+        let mut mesh = quad();
+        mesh.compute_normals();
+        assert_eq!(mesh.normals, [[0.0, 0.0, 1.0]; 4]);
+
+        mesh.indices = vec![0, 2, 1];
+        mesh.compute_normals();
+        assert_eq!(mesh.normals[0], [0.0, 0.0, -1.0]);
+        // Not part of any triangle.
+        assert_eq!(mesh.normals[3], [0.0, 1.0, 0.0]);
+    }
+
+    #[test]
+    fn compute_tangents_follow_u_and_flip_with_v() {
+        let mut mesh = quad();
+        mesh.compute_normals();
+        mesh.compute_tangents();
+        assert_eq!(mesh.tangents, [[1.0, 0.0, 0.0, 1.0]; 4]);
+
+        for tex_coord in &mut mesh.tex_coords {
+            tex_coord[1] = 1.0 - tex_coord[1];
+        }
+        mesh.compute_tangents();
+        assert_eq!(mesh.tangents, [[1.0, 0.0, 0.0, -1.0]; 4]);
+    }
+
+    #[test]
+    fn compute_tangents_needs_normals_and_tex_coords() {
+        let quad = quad();
+        let mut mesh = Mesh {
+            positions: quad.positions.clone(),
+            tex_coords: quad.tex_coords.clone(),
+            indices: quad.indices.clone(),
+            ..Mesh::new()
+        };
+        mesh.compute_tangents();
+        assert!(mesh.tangents.is_empty());
+    }
+}
@@ -0,0 +1,159 @@
+use std::{error::Error, time::Duration};
+
+use glium::{
+    framebuffer::{DepthRenderBuffer, SimpleFrameBuffer},
+    texture::{DepthFormat, MipmapsOption, SrgbFormat, SrgbTexture2d},
+};
+use image::RgbaImage;
+
+use crate::{
+    Clock, Drawable, FixedClock, FrameContext, InputState, Resources, Timestep,
+    headless::{Headless, to_rgba_image},
+    timestep::MAX_FRAME_DELTA,
+    window::run_updates,
+};
+
+/// Runs a `Drawable` against a surfaceless EGL context and renders into an offscreen
+/// framebuffer, so scenes can be drawn without a display (Mesa's llvmpipe works fine).
+///
+/// The color target is sRGB like the window's, so captures match what is shown on screen.
+pub struct Offscreen<T: Drawable, C: Clock = FixedClock> {
+    impl_: T,
+    context: Headless,
+    resources: Resources,
+    color: SrgbTexture2d,
+    depth: DepthRenderBuffer,
+    clock: C,
+    timestep: Timestep,
+    input: InputState,
+}
+
+impl<T: Drawable> Offscreen<T> {
+    /// Renders with a fixed 60 Hz clock.
+    pub fn new(impl_: T, width: u32, height: u32) -> Result<Self, Box<dyn Error>> {
+        let clock = FixedClock::new(Duration::from_secs(1) / 60);
+        Self::with_clock(impl_, width, height, clock)
+    }
+}
+
+impl<T: Drawable, C: Clock> Offscreen<T, C> {
+    pub fn with_clock(
+        mut impl_: T,
+        width: u32,
+        height: u32,
+        clock: C,
+    ) -> Result<Self, Box<dyn Error>> {
+        let context = crate::headless::context()?;
+
+        let color = SrgbTexture2d::empty_with_format(
+            &context,
+            SrgbFormat::U8U8U8U8,
+            MipmapsOption::NoMipmap,
+            width,
+            height,
+        )?;
+        let depth = DepthRenderBuffer::new(&context, DepthFormat::I24, width, height)?;
+
+        let mut resources = Resources::new();
+        impl_.init(&context, &mut resources);
+        Ok(Self {
+            impl_,
+            context,
+            resources,
+            color,
+            depth,
+            clock,
+            timestep: Timestep::new(Duration::from_secs(1) / 60).with_max_delta(MAX_FRAME_DELTA),
+            input: InputState::new(),
+        })
+    }
+
+    pub fn drawable(&mut self) -> &mut T {
+        &mut self.impl_
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Input seen by the next `Drawable::update`, for injecting synthetic key presses.
+    pub fn input_mut(&mut self) -> &mut InputState {
+        &mut self.input
+    }
+
+    /// Sets the length of one `Drawable::update` step. Panics if `step` is zero.
+    pub fn set_timestep(&mut self, step: Duration) {
+        self.timestep = Timestep::new(step).with_max_delta(MAX_FRAME_DELTA);
+    }
+
+    /// Advances the clock by one tick, runs the updates that are due and renders a frame.
+    pub fn render(&mut self) -> RgbaImage {
+        let delta = self.clock.tick();
+        run_updates(&mut self.impl_, &mut self.timestep, delta, &mut self.input);
+
+        let mut target =
+            SimpleFrameBuffer::with_depth_buffer(&self.context, &self.color, &self.depth).unwrap();
+        let context = FrameContext {
+            size: (self.color.width(), self.color.height()),
+            delta,
+            elapsed: self.clock.elapsed(),
+            alpha: self.timestep.alpha(),
+            resources: &self.resources,
+        };
+        self.impl_.draw(&mut target, &context);
+
+        to_rgba_image(self.color.read())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glium::{Rect, Surface};
+
+    use super::*;
+
+    /// Clears the frame to `top` with the bottom half cleared to `bottom`.
+    struct Halves {
+        top: [f32; 4],
+        bottom: [f32; 4],
+    }
+
+    impl Drawable for Halves {
+        fn draw<S: Surface>(&mut self, target: &mut S, _context: &FrameContext) {
+            let (width, height) = target.get_dimensions();
+            let [r, g, b, a] = self.top;
+            target.clear_color_and_depth((r, g, b, a), 1.0);
+            let [r, g, b, a] = self.bottom;
+            let rect = Rect {
+                left: 0,
+                bottom: 0,
+                width,
+                height: height / 2,
+            };
+            target.clear(Some(&rect), Some((r, g, b, a)), false, None, None);
+        }
+    }
+
+    #[test]
+    fn render_reads_back_the_frame_top_down() {
+        let halves = Halves {
+            top: [1.0, 0.0, 0.0, 1.0],
+            bottom: [0.0, 1.0, 0.0, 1.0],
+        };
+        let mut offscreen = Offscreen::new(halves, 32, 16).unwrap();
+
+        let image = offscreen.render();
+        assert_eq!(image.dimensions(), (32, 16));
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(31, 7).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(0, 8).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(31, 15).0, [0, 255, 0, 255]);
+
+        offscreen.drawable().top = [0.0, 0.0, 1.0, 1.0];
+        assert_eq!(offscreen.render().get_pixel(0, 0).0, [0, 0, 255, 255]);
+    }
+}
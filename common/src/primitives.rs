@@ -0,0 +1,436 @@
+//! Procedural meshes with normals, texture coordinates and tangents.
+//!
+//! All shapes are centered on the origin, use counter-clockwise front faces seen from outside
+//! and map `v = 0` to the bottom of the texture.
+
+use std::{collections::HashMap, f32::consts::PI};
+
+use crate::{
+    Mesh, MeshVertex,
+    vector::{add, normalize, scale},
+};
+
+/// Axis-aligned cube with edge length `size`, each face mapping the full texture.
+pub fn cube(size: f32) -> Mesh {
+    // (normal, u axis, v axis) with u x v = normal.
+    const FACES: [[[f32; 3]; 3]; 6] = [
+        [[1.0, 0.0, 0.0], [0.0, 0.0, -1.0], [0.0, 1.0, 0.0]],
+        [[-1.0, 0.0, 0.0], [0.0, 0.0, 1.0], [0.0, 1.0, 0.0]],
+        [[0.0, 1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, -1.0]],
+        [[0.0, -1.0, 0.0], [1.0, 0.0, 0.0], [0.0, 0.0, 1.0]],
+        [[0.0, 0.0, 1.0], [1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+        [[0.0, 0.0, -1.0], [-1.0, 0.0, 0.0], [0.0, 1.0, 0.0]],
+    ];
+    const CORNERS: [[f32; 2]; 4] = [[-1.0, -1.0], [1.0, -1.0], [1.0, 1.0], [-1.0, 1.0]];
+
+    let half = size / 2.0;
+    let mut mesh = Mesh::new();
+    for [normal, u, v] in FACES {
+        let first = mesh.vertex_count() as u32;
+        for [s, t] in CORNERS {
+            let position = scale(add(normal, add(scale(u, s), scale(v, t))), half);
+            mesh.push_vertex(
+                MeshVertex::new(position)
+                    .with_normal(normal)
+                    .with_tex_coord([(s + 1.0) / 2.0, (t + 1.0) / 2.0]),
+            );
+        }
+        mesh.push_triangle(first, first + 1, first + 2);
+        mesh.push_triangle(first, first + 2, first + 3);
+    }
+    mesh.compute_tangents();
+    mesh
+}
+
+/// Unit direction for the sphere angles: `polar` from +Y, `azimuth` from +Z towards +X.
+fn sphere_direction(polar: f32, azimuth: f32) -> [f32; 3] {
+    [
+        polar.sin() * azimuth.sin(),
+        polar.cos(),
+        polar.sin() * azimuth.cos(),
+    ]
+}
+
+/// Latitude/longitude sphere. The seam column is duplicated so the texture wraps once.
+pub fn uv_sphere(radius: f32, segments: u32, rings: u32) -> Mesh {
+    let segments = segments.max(3);
+    let rings = rings.max(2);
+
+    let mut mesh = Mesh::new();
+    for ring in 0..=rings {
+        let v = ring as f32 / rings as f32;
+        for segment in 0..=segments {
+            let u = segment as f32 / segments as f32;
+            let normal = sphere_direction(v * PI, u * 2.0 * PI);
+            mesh.push_vertex(
+                MeshVertex::new(scale(normal, radius))
+                    .with_normal(normal)
+                    .with_tex_coord([u, 1.0 - v]),
+            );
+        }
+    }
+
+    let row = segments + 1;
+    for ring in 0..rings {
+        for segment in 0..segments {
+            let a = ring * row + segment;
+            let b = a + row;
+            // The first and last rings meet at a pole, skip the degenerate half of their quads.
+            if ring != rings - 1 {
+                mesh.push_triangle(a, b, b + 1);
+            }
+            if ring != 0 {
+                mesh.push_triangle(a, b + 1, a + 1);
+            }
+        }
+    }
+    mesh.compute_tangents();
+    mesh
+}
+
+/// Subdivided icosahedron, more even than [`uv_sphere`]. `subdivisions` of 0 is the plain
+/// icosahedron, each level quadruples the triangle count.
+pub fn icosphere(radius: f32, subdivisions: u32) -> Mesh {
+    let t = (1.0 + 5.0_f32.sqrt()) / 2.0;
+    let mut positions = [
+        [-1.0, t, 0.0],
+        [1.0, t, 0.0],
+        [-1.0, -t, 0.0],
+        [1.0, -t, 0.0],
+        [0.0, -1.0, t],
+        [0.0, 1.0, t],
+        [0.0, -1.0, -t],
+        [0.0, 1.0, -t],
+        [t, 0.0, -1.0],
+        [t, 0.0, 1.0],
+        [-t, 0.0, -1.0],
+        [-t, 0.0, 1.0],
+    ]
+    .map(normalize)
+    .to_vec();
+    let mut triangles: Vec<[usize; 3]> = vec![
+        [0, 11, 5],
+        [0, 5, 1],
+        [0, 1, 7],
+        [0, 7, 10],
+        [0, 10, 11],
+        [1, 5, 9],
+        [5, 11, 4],
+        [11, 10, 2],
+        [10, 7, 6],
+        [7, 1, 8],
+        [3, 9, 4],
+        [3, 4, 2],
+        [3, 2, 6],
+        [3, 6, 8],
+        [3, 8, 9],
+        [4, 9, 5],
+        [2, 4, 11],
+        [6, 2, 10],
+        [8, 6, 7],
+        [9, 8, 1],
+    ];
+
+    for _ in 0..subdivisions {
+        let mut midpoints = HashMap::new();
+        let mut midpoint = |a: usize, b: usize| {
+            *midpoints.entry((a.min(b), a.max(b))).or_insert_with(|| {
+                positions.push(normalize(add(positions[a], positions[b])));
+                positions.len() - 1
+            })
+        };
+        triangles = triangles
+            .into_iter()
+            .flat_map(|[a, b, c]| {
+                let (ab, bc, ca) = (midpoint(a, b), midpoint(b, c), midpoint(c, a));
+                [[a, ab, ca], [b, bc, ab], [c, ca, bc], [ab, bc, ca]]
+            })
+            .collect();
+    }
+
+    // Texture coordinates are assigned per triangle so the ones crossing the seam can wrap,
+    // shared corners are merged again by `from_triangles`.
+    let vertices = triangles.into_iter().flat_map(|triangle| {
+        let normals = triangle.map(|i| positions[i]);
+        let mut tex_coords = normals.map(|n| {
+            let u = (n[0].atan2(n[2]) / (2.0 * PI)).rem_euclid(1.0);
+            [u, 1.0 - n[1].clamp(-1.0, 1.0).acos() / PI]
+        });
+        let (min, max) = tex_coords
+            .iter()
+            .fold((f32::MAX, f32::MIN), |(min, max), uv| {
+                (min.min(uv[0]), max.max(uv[0]))
+            });
+        if max - min > 0.5 {
+            for uv in &mut tex_coords {
+                if uv[0] < 0.5 {
+                    uv[0] += 1.0;
+                }
+            }
+        }
+        (0..3).map(move |i| {
+            MeshVertex::new(scale(normals[i], radius))
+                .with_normal(normals[i])
+                .with_tex_coord(tex_coords[i])
+        })
+    });
+    let mut mesh = Mesh::from_triangles(vertices);
+    mesh.compute_tangents();
+    mesh
+}
+
+/// Flat grid in the XZ plane facing +Y, split into `columns` x `rows` quads.
+pub fn plane(width: f32, depth: f32, columns: u32, rows: u32) -> Mesh {
+    let columns = columns.max(1);
+    let rows = rows.max(1);
+
+    let mut mesh = Mesh::new();
+    for row in 0..=rows {
+        let v = row as f32 / rows as f32;
+        for column in 0..=columns {
+            let u = column as f32 / columns as f32;
+            let position = [(u - 0.5) * width, 0.0, (0.5 - v) * depth];
+            mesh.push_vertex(
+                MeshVertex::new(position)
+                    .with_normal([0.0, 1.0, 0.0])
+                    .with_tex_coord([u, v]),
+            );
+        }
+    }
+
+    let stride = columns + 1;
+    for row in 0..rows {
+        for column in 0..columns {
+            let a = row * stride + column;
+            let d = a + stride;
+            mesh.push_triangle(a, a + 1, d + 1);
+            mesh.push_triangle(a, d + 1, d);
+        }
+    }
+    mesh.compute_tangents();
+    mesh
+}
+
+/// Adds a flat disc at height `y` facing up or down.
+fn push_cap(mesh: &mut Mesh, radius: f32, y: f32, segments: u32, up: bool) {
+    let normal = [0.0, if up { 1.0 } else { -1.0 }, 0.0];
+    let center = mesh.push_vertex(
+        MeshVertex::new([0.0, y, 0.0])
+            .with_normal(normal)
+            .with_tex_coord([0.5, 0.5]),
+    );
+    for segment in 0..=segments {
+        let angle = segment as f32 / segments as f32 * 2.0 * PI;
+        let (sin, cos) = angle.sin_cos();
+        let v = if up { 0.5 - cos / 2.0 } else { 0.5 + cos / 2.0 };
+        mesh.push_vertex(
+            MeshVertex::new([sin * radius, y, cos * radius])
+                .with_normal(normal)
+                .with_tex_coord([0.5 + sin / 2.0, v]),
+        );
+    }
+    for segment in 0..segments {
+        let a = center + 1 + segment;
+        if up {
+            mesh.push_triangle(center, a, a + 1);
+        } else {
+            mesh.push_triangle(center, a + 1, a);
+        }
+    }
+}
+
+/// Capped cylinder along Y.
+pub fn cylinder(radius: f32, height: f32, segments: u32) -> Mesh {
+    let segments = segments.max(3);
+    let half = height / 2.0;
+
+    let mut mesh = Mesh::new();
+    for segment in 0..=segments {
+        let u = segment as f32 / segments as f32;
+        let (sin, cos) = (u * 2.0 * PI).sin_cos();
+        let normal = [sin, 0.0, cos];
+        for (y, v) in [(-half, 0.0), (half, 1.0)] {
+            mesh.push_vertex(
+                MeshVertex::new([sin * radius, y, cos * radius])
+                    .with_normal(normal)
+                    .with_tex_coord([u, v]),
+            );
+        }
+    }
+    for segment in 0..segments {
+        let bottom = segment * 2;
+        let top = bottom + 1;
+        mesh.push_triangle(bottom, bottom + 2, top + 2);
+        mesh.push_triangle(bottom, top + 2, top);
+    }
+
+    push_cap(&mut mesh, radius, half, segments, true);
+    push_cap(&mut mesh, radius, -half, segments, false);
+    mesh.compute_tangents();
+    mesh
+}
+
+/// Cone along Y with its apex at `height / 2` and a capped base.
+pub fn cone(radius: f32, height: f32, segments: u32) -> Mesh {
+    let segments = segments.max(3);
+    let half = height / 2.0;
+    let slant = |angle: f32| {
+        let (sin, cos) = angle.sin_cos();
+        normalize([sin * height, radius, cos * height])
+    };
+
+    let mut mesh = Mesh::new();
+    for segment in 0..=segments {
+        let u = segment as f32 / segments as f32;
+        let angle = u * 2.0 * PI;
+        let (sin, cos) = angle.sin_cos();
+        mesh.push_vertex(
+            MeshVertex::new([sin * radius, -half, cos * radius])
+                .with_normal(slant(angle))
+                .with_tex_coord([u, 0.0]),
+        );
+    }
+    // One apex per segment so each keeps the normal of its own face.
+    let apex = mesh.vertex_count() as u32;
+    for segment in 0..segments {
+        let u = (segment as f32 + 0.5) / segments as f32;
+        mesh.push_vertex(
+            MeshVertex::new([0.0, half, 0.0])
+                .with_normal(slant(u * 2.0 * PI))
+                .with_tex_coord([u, 1.0]),
+        );
+    }
+    for segment in 0..segments {
+        mesh.push_triangle(segment, segment + 1, apex + segment);
+    }
+
+    push_cap(&mut mesh, radius, -half, segments, false);
+    mesh.compute_tangents();
+    mesh
+}
+
+/// Ring around Y. `major_radius` is the distance from the center to the middle of the tube.
+pub fn torus(
+    major_radius: f32,
+    minor_radius: f32,
+    major_segments: u32,
+    minor_segments: u32,
+) -> Mesh {
+    let major_segments = major_segments.max(3);
+    let minor_segments = minor_segments.max(3);
+
+    let mut mesh = Mesh::new();
+    for i in 0..=major_segments {
+        let u = i as f32 / major_segments as f32;
+        let (sin, cos) = (u * 2.0 * PI).sin_cos();
+        for j in 0..=minor_segments {
+            let v = j as f32 / minor_segments as f32;
+            let (tube_sin, tube_cos) = (v * 2.0 * PI).sin_cos();
+            let normal = [tube_cos * sin, tube_sin, tube_cos * cos];
+            let position = add(
+                [sin * major_radius, 0.0, cos * major_radius],
+                scale(normal, minor_radius),
+            );
+            mesh.push_vertex(
+                MeshVertex::new(position)
+                    .with_normal(normal)
+                    .with_tex_coord([u, v]),
+            );
+        }
+    }
+
+    let row = minor_segments + 1;
+    for i in 0..major_segments {
+        for j in 0..minor_segments {
+            let a = i * row + j;
+            let b = a + row;
+            mesh.push_triangle(a, b, b + 1);
+            mesh.push_triangle(a, b + 1, a + 1);
+        }
+    }
+    mesh.compute_tangents();
+    mesh
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::vector::{cross, dot, length, sub};
+
+    fn shapes() -> Vec<(&'static str, Mesh)> {
+        vec![
+            ("cube", cube(2.0)),
+            ("uv_sphere", uv_sphere(1.0, 16, 8)),
+            ("icosphere", icosphere(1.0, 2)),
+            ("plane", plane(2.0, 3.0, 4, 2)),
+            ("cylinder", cylinder(1.0, 2.0, 12)),
+            ("cone", cone(1.0, 2.0, 12)),
+            ("torus", torus(1.0, 0.25, 16, 8)),
+        ]
+    }
+
+    #[test]
+    fn vertex_and_triangle_counts() {
+        let counts = |mesh: Mesh| (mesh.vertex_count(), mesh.triangle_count());
+        assert_eq!(counts(cube(1.0)), (24, 12));
+        assert_eq!(counts(uv_sphere(1.0, 16, 8)), (17 * 9, 2 * 16 * 7));
+        assert_eq!(counts(plane(1.0, 1.0, 4, 2)), (5 * 3, 2 * 4 * 2));
+        assert_eq!(counts(cylinder(1.0, 1.0, 12)), (2 * 13 + 2 * 14, 4 * 12));
+        assert_eq!(counts(cone(1.0, 1.0, 12)), (13 + 12 + 14, 2 * 12));
+        assert_eq!(counts(torus(1.0, 0.25, 16, 8)), (17 * 9, 2 * 16 * 8));
+        assert_eq!(icosphere(1.0, 0).triangle_count(), 20);
+        assert_eq!(icosphere(1.0, 2).triangle_count(), 20 * 16);
+    }
+
+    #[test]
+    fn segment_counts_are_clamped() {
+        assert_eq!(uv_sphere(1.0, 0, 0).triangle_count(), 2 * 3);
+        assert_eq!(plane(1.0, 1.0, 0, 0).triangle_count(), 2);
+        assert_eq!(cylinder(1.0, 1.0, 1).triangle_count(), 4 * 3);
+    }
+
+    #[test]
+    fn normals_and_tangents_are_unit_length() {
+        for (name, mesh) in shapes() {
+            assert_eq!(mesh.normals.len(), mesh.vertex_count(), "{name}");
+            assert_eq!(mesh.tangents.len(), mesh.vertex_count(), "{name}");
+            for (normal, tangent) in mesh.normals.iter().zip(&mesh.tangents) {
+                assert!((length(*normal) - 1.0).abs() < 1e-4, "{name}: {normal:?}");
+                let xyz = [tangent[0], tangent[1], tangent[2]];
+                assert!((length(xyz) - 1.0).abs() < 1e-4, "{name}: {tangent:?}");
+                assert!(dot(xyz, *normal).abs() < 1e-3, "{name}: {tangent:?}");
+                assert_eq!(tangent[3].abs(), 1.0, "{name}: {tangent:?}");
+            }
+        }
+    }
+
+    #[test]
+    fn triangles_wind_counter_clockwise_around_their_normals() {
+        for (name, mesh) in shapes() {
+            for triangle in mesh.indices.chunks_exact(3) {
+                let [a, b, c] = [0, 1, 2].map(|i| triangle[i] as usize);
+                let face = cross(
+                    sub(mesh.positions[b], mesh.positions[a]),
+                    sub(mesh.positions[c], mesh.positions[a]),
+                );
+                let normal = [a, b, c]
+                    .iter()
+                    .fold([0.0; 3], |sum, &i| add(sum, mesh.normals[i]));
+                assert!(length(face) > 1e-8, "{name}: degenerate {triangle:?}");
+                assert!(
+                    dot(face, normal) > 0.0,
+                    "{name}: {triangle:?} winds clockwise"
+                );
+            }
+        }
+    }
+
+    #[test]
+    fn closed_shapes_face_outwards() {
+        for mesh in [cube(2.0), uv_sphere(1.0, 16, 8), icosphere(1.0, 1)] {
+            for (position, normal) in mesh.positions.iter().zip(&mesh.normals) {
+                assert!(dot(*position, *normal) > 0.0);
+            }
+        }
+    }
+}
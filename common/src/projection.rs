@@ -0,0 +1,277 @@
+use glium::winit::event::WindowEvent;
+
+use crate::DepthConvention;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ProjectionKind {
+    /// Field of view in degrees.
+    Perspective { fov: f32, near: f32, far: f32 },
+    /// `height` is the visible extent along y, the width follows the aspect ratio.
+    Orthographic { height: f32, near: f32, far: f32 },
+    /// Perspective with the far plane at infinity and depth running from 1 at `near` to 0 at
+    /// infinity. Needs a depth clear of 0.0 and `DepthTest::IfMore`.
+    ///
+    /// The precision gain also needs a 0..1 clip range (`glClipControl` with
+    /// `GL_ZERO_TO_ONE`) and a floating point depth buffer. Neither the window nor
+    /// `Offscreen` sets those up, so with the default -1..1 range and a 24-bit integer
+    /// buffer this only flips the depth comparison of an infinite projection.
+    ReverseZ { fov: f32, near: f32 },
+}
+
+/// Projection matrix kept in sync with the window aspect ratio.
+#[derive(Clone, Debug)]
+pub struct Projection {
+    kind: ProjectionKind,
+    aspect: f32,
+    matrix: mats::Mat4<f32>,
+    inverse: mats::Mat4<f32>,
+}
+
+impl Default for Projection {
+    fn default() -> Self {
+        Self::perspective(45.0, 0.1, 100.0)
+    }
+}
+
+impl Projection {
+    pub fn new(kind: ProjectionKind) -> Self {
+        let (matrix, inverse) = compute(kind, 1.0);
+        Self {
+            kind,
+            aspect: 1.0,
+            matrix,
+            inverse,
+        }
+    }
+
+    pub fn perspective(fov: f32, near: f32, far: f32) -> Self {
+        Self::new(ProjectionKind::Perspective { fov, near, far })
+    }
+
+    pub fn orthographic(height: f32, near: f32, far: f32) -> Self {
+        Self::new(ProjectionKind::Orthographic { height, near, far })
+    }
+
+    pub fn reverse_z(fov: f32, near: f32) -> Self {
+        Self::new(ProjectionKind::ReverseZ { fov, near })
+    }
+
+    pub fn kind(&self) -> ProjectionKind {
+        self.kind
+    }
+
+    pub fn set_kind(&mut self, kind: ProjectionKind) {
+        self.kind = kind;
+        self.recompute();
+    }
+
+    pub fn aspect(&self) -> f32 {
+        self.aspect
+    }
+
+    pub fn set_aspect(&mut self, aspect: f32) {
+        if aspect != self.aspect && aspect.is_finite() && aspect > 0.0 {
+            self.aspect = aspect;
+            self.recompute();
+        }
+    }
+
+    /// Picks up the new aspect ratio from `WindowEvent::Resized`, ignoring minimized windows.
+    pub fn handle_event(&mut self, event: &WindowEvent) {
+        if let WindowEvent::Resized(size) = event
+            && size.width > 0
+            && size.height > 0
+        {
+            self.set_aspect(size.width as f32 / size.height as f32);
+        }
+    }
+
+    pub fn is_reverse_z(&self) -> bool {
+        matches!(self.kind, ProjectionKind::ReverseZ { .. })
+    }
+
+    pub fn depth_convention(&self) -> DepthConvention {
+        if self.is_reverse_z() {
+            DepthConvention::Reversed
+        } else {
+            DepthConvention::Standard
+        }
+    }
+
+    pub fn matrix(&self) -> mats::Mat4<f32> {
+        self.matrix
+    }
+
+    /// Maps clip space back to view space, for unprojecting cursor positions when picking.
+    pub fn inverse(&self) -> mats::Mat4<f32> {
+        self.inverse
+    }
+
+    fn recompute(&mut self) {
+        (self.matrix, self.inverse) = compute(self.kind, self.aspect);
+    }
+}
+
+/// Builds a matrix from rows in the usual column-vector notation, as the shaders use it.
+/// `mats::Mat4` stores rows and uploads their transpose, the columns glium expects.
+fn from_rows(rows: [[f32; 4]; 4]) -> mats::Mat4<f32> {
+    mats::Mat4::from(rows)
+}
+
+fn compute(kind: ProjectionKind, aspect: f32) -> (mats::Mat4<f32>, mats::Mat4<f32>) {
+    match kind {
+        ProjectionKind::Perspective { fov, near, far } => {
+            let y = 1.0 / (fov.to_radians() / 2.0).tan();
+            let x = y / aspect;
+            let a = (far + near) / (near - far);
+            let b = 2.0 * far * near / (near - far);
+            let matrix = from_rows([
+                [x, 0.0, 0.0, 0.0],
+                [0.0, y, 0.0, 0.0],
+                [0.0, 0.0, a, b],
+                [0.0, 0.0, -1.0, 0.0],
+            ]);
+            let inverse = from_rows([
+                [1.0 / x, 0.0, 0.0, 0.0],
+                [0.0, 1.0 / y, 0.0, 0.0],
+                [0.0, 0.0, 0.0, -1.0],
+                [0.0, 0.0, 1.0 / b, a / b],
+            ]);
+            (matrix, inverse)
+        }
+        ProjectionKind::Orthographic { height, near, far } => {
+            let x = 2.0 / (height * aspect);
+            let y = 2.0 / height;
+            let z = -2.0 / (far - near);
+            let w = -(far + near) / (far - near);
+            let matrix = from_rows([
+                [x, 0.0, 0.0, 0.0],
+                [0.0, y, 0.0, 0.0],
+                [0.0, 0.0, z, w],
+                [0.0, 0.0, 0.0, 1.0],
+            ]);
+            let inverse = from_rows([
+                [1.0 / x, 0.0, 0.0, 0.0],
+                [0.0, 1.0 / y, 0.0, 0.0],
+                [0.0, 0.0, 1.0 / z, -w / z],
+                [0.0, 0.0, 0.0, 1.0],
+            ]);
+            (matrix, inverse)
+        }
+        ProjectionKind::ReverseZ { fov, near } => {
+            // z_ndc = -1 - 2 * near / z_view: 1 at the near plane, -1 at infinity.
+            let y = 1.0 / (fov.to_radians() / 2.0).tan();
+            let x = y / aspect;
+            let b = 2.0 * near;
+            let matrix = from_rows([
+                [x, 0.0, 0.0, 0.0],
+                [0.0, y, 0.0, 0.0],
+                [0.0, 0.0, 1.0, b],
+                [0.0, 0.0, -1.0, 0.0],
+            ]);
+            let inverse = from_rows([
+                [1.0 / x, 0.0, 0.0, 0.0],
+                [0.0, 1.0 / y, 0.0, 0.0],
+                [0.0, 0.0, 0.0, -1.0],
+                [0.0, 0.0, 1.0 / b, 1.0 / b],
+            ]);
+            (matrix, inverse)
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glium::uniforms::{AsUniformValue, UniformValue};
+
+    use super::*;
+
+    /// The columns glium uploads for `matrix`.
+    fn columns(matrix: mats::Mat4<f32>) -> [[f32; 4]; 4] {
+        match matrix.as_uniform_value() {
+            UniformValue::Mat4(columns) => columns,
+            _ => unreachable!("mats matrices upload as Mat4"),
+        }
+    }
+
+    fn assert_close(actual: mats::Mat4<f32>, expected: mats::Mat4<f32>) {
+        let (actual, expected) = (columns(actual), columns(expected));
+        for (a, e) in actual.iter().flatten().zip(expected.iter().flatten()) {
+            assert!(
+                (a - e).abs() <= 1e-5 * e.abs().max(1.0),
+                "{actual:?} != {expected:?}"
+            );
+        }
+    }
+
+    fn identity() -> mats::Mat4<f32> {
+        from_rows([
+            [1.0, 0.0, 0.0, 0.0],
+            [0.0, 1.0, 0.0, 0.0],
+            [0.0, 0.0, 1.0, 0.0],
+            [0.0, 0.0, 0.0, 1.0],
+        ])
+    }
+
+    #[test]
+    fn from_rows_uploads_columns() {
+        let matrix = from_rows([
+            [1.0, 2.0, 3.0, 4.0],
+            [5.0, 6.0, 7.0, 8.0],
+            [9.0, 10.0, 11.0, 12.0],
+            [13.0, 14.0, 15.0, 16.0],
+        ]);
+        assert_eq!(columns(matrix)[0], [1.0, 5.0, 9.0, 13.0]);
+        assert_eq!(columns(matrix)[3], [4.0, 8.0, 12.0, 16.0]);
+    }
+
+    #[test]
+    fn default_matches_mats_perspective() {
+        let mut projection = Projection::default();
+        projection.set_aspect(1.6);
+        assert_close(
+            projection.matrix(),
+            mats::perspective(45f32.to_radians(), 1.6, 0.1, 100.0),
+        );
+        // Column 2 carries -z into w.
+        assert_eq!(columns(projection.matrix())[2][3], -1.0);
+    }
+
+    #[test]
+    fn inverse_undoes_matrix() {
+        let kinds = [
+            ProjectionKind::Perspective {
+                fov: 60.0,
+                near: 0.5,
+                far: 50.0,
+            },
+            ProjectionKind::Orthographic {
+                height: 4.0,
+                near: 0.1,
+                far: 20.0,
+            },
+            ProjectionKind::ReverseZ {
+                fov: 60.0,
+                near: 0.5,
+            },
+        ];
+        for kind in kinds {
+            let mut projection = Projection::new(kind);
+            projection.set_aspect(16.0 / 9.0);
+            assert_close(projection.matrix() * projection.inverse(), identity());
+            assert_close(projection.inverse() * projection.matrix(), identity());
+        }
+    }
+
+    #[test]
+    fn depth_convention_follows_kind() {
+        assert_eq!(
+            Projection::default().depth_convention(),
+            DepthConvention::Standard
+        );
+        assert_eq!(
+            Projection::reverse_z(45.0, 0.1).depth_convention(),
+            DepthConvention::Reversed
+        );
+    }
+}
@@ -0,0 +1,255 @@
+use std::{error::Error, fmt};
+
+use glium::{Blend, Depth, DepthTest, DrawParameters, PolygonMode, Surface};
+
+/// Which end of the depth range is "far".
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DepthConvention {
+    /// Clear to 1.0, nearer fragments have smaller depth.
+    Standard,
+    /// Clear to 0.0, nearer fragments have larger depth. Used with reverse-Z projections.
+    Reversed,
+}
+
+impl DepthConvention {
+    pub fn clear_value(self) -> f32 {
+        match self {
+            DepthConvention::Standard => 1.0,
+            DepthConvention::Reversed => 0.0,
+        }
+    }
+
+    pub fn test(self) -> DepthTest {
+        match self {
+            DepthConvention::Standard => DepthTest::IfLess,
+            DepthConvention::Reversed => DepthTest::IfMore,
+        }
+    }
+}
+
+#[derive(Debug)]
+pub enum RenderStateError {
+    /// The depth clear value would make every fragment fail (or pass) the depth test.
+    ClearMismatch {
+        clear: f32,
+        test: DepthTest,
+    },
+    /// Depth writes are enabled but the test discards every fragment.
+    WriteIgnored,
+    InvalidClear(f32),
+}
+
+impl fmt::Display for RenderStateError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            RenderStateError::ClearMismatch { clear, test } => {
+                write!(
+                    f,
+                    "depth clear value {clear} does not match depth test {test:?}"
+                )
+            }
+            RenderStateError::WriteIgnored => {
+                write!(f, "depth writes are enabled with DepthTest::Ignore")
+            }
+            RenderStateError::InvalidClear(clear) => write!(f, "invalid depth clear value {clear}"),
+        }
+    }
+}
+
+impl Error for RenderStateError {}
+
+/// Depth clear value and draw parameters that belong together.
+///
+/// The presets are consistent by construction; [`RenderState::new`] checks custom combinations.
+#[derive(Clone, Debug)]
+pub struct RenderState {
+    clear_depth: Option<f32>,
+    depth: Depth,
+    blend: Blend,
+    polygon_mode: PolygonMode,
+}
+
+impl RenderState {
+    pub fn new(clear_depth: Option<f32>, depth: Depth) -> Result<Self, RenderStateError> {
+        if let Some(clear) = clear_depth {
+            if clear.is_nan() {
+                return Err(RenderStateError::InvalidClear(clear));
+            }
+            // GL clamps the clear value into the depth range.
+            let clamped = clear.clamp(0.0, 1.0);
+            let consistent = match depth.test {
+                DepthTest::IfLess | DepthTest::IfLessOrEqual => clamped == 1.0,
+                DepthTest::IfMore | DepthTest::IfMoreOrEqual => clamped == 0.0,
+                _ => true,
+            };
+            if !consistent {
+                return Err(RenderStateError::ClearMismatch {
+                    clear,
+                    test: depth.test,
+                });
+            }
+        }
+        if depth.write && depth.test == DepthTest::Ignore {
+            return Err(RenderStateError::WriteIgnored);
+        }
+
+        Ok(Self {
+            clear_depth,
+            depth,
+            blend: Blend::default(),
+            polygon_mode: PolygonMode::Fill,
+        })
+    }
+
+    /// Clears depth and tests and writes every fragment.
+    pub fn opaque(convention: DepthConvention) -> Self {
+        Self {
+            clear_depth: Some(convention.clear_value()),
+            depth: Depth {
+                test: convention.test(),
+                write: true,
+                ..Default::default()
+            },
+            blend: Blend::default(),
+            polygon_mode: PolygonMode::Fill,
+        }
+    }
+
+    /// Tests against the depth left by an opaque pass without writing, alpha blended.
+    pub fn transparent(convention: DepthConvention) -> Self {
+        Self {
+            clear_depth: None,
+            depth: Depth {
+                test: convention.test(),
+                write: false,
+                ..Default::default()
+            },
+            blend: Blend::alpha_blending(),
+            polygon_mode: PolygonMode::Fill,
+        }
+    }
+
+    /// [`opaque`](Self::opaque) drawn as lines. Lines only hide the lines they pass in front
+    /// of, so the back edges of a lone mesh stay visible.
+    pub fn wireframe(convention: DepthConvention) -> Self {
+        Self {
+            polygon_mode: PolygonMode::Line,
+            ..Self::opaque(convention)
+        }
+    }
+
+    /// Drawn on top of everything, ignoring depth.
+    pub fn overlay() -> Self {
+        Self {
+            clear_depth: None,
+            depth: Depth::default(),
+            blend: Blend::alpha_blending(),
+            polygon_mode: PolygonMode::Fill,
+        }
+    }
+
+    pub fn clear_depth(&self) -> Option<f32> {
+        self.clear_depth
+    }
+
+    /// Clears the color buffer, and the depth buffer if this state owns it.
+    pub fn clear<S: Surface>(&self, target: &mut S, color: (f32, f32, f32, f32)) {
+        match self.clear_depth {
+            Some(depth) => target.clear_color_and_depth(color, depth),
+            None => target.clear_color(color.0, color.1, color.2, color.3),
+        }
+    }
+
+    pub fn draw_parameters(&self) -> DrawParameters<'static> {
+        DrawParameters {
+            depth: self.depth,
+            blend: self.blend,
+            polygon_mode: self.polygon_mode,
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONVENTIONS: [DepthConvention; 2] =
+        [DepthConvention::Standard, DepthConvention::Reversed];
+
+    fn depth(test: DepthTest, write: bool) -> Depth {
+        Depth {
+            test,
+            write,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn presets_pass_validation() {
+        for convention in CONVENTIONS {
+            let presets = [
+                RenderState::opaque(convention),
+                RenderState::transparent(convention),
+                RenderState::wireframe(convention),
+                RenderState::overlay(),
+            ];
+            for preset in presets {
+                let parameters = preset.draw_parameters();
+                RenderState::new(preset.clear_depth(), parameters.depth).unwrap();
+            }
+        }
+    }
+
+    #[test]
+    fn presets_follow_the_convention() {
+        for convention in CONVENTIONS {
+            let opaque = RenderState::opaque(convention);
+            assert_eq!(opaque.clear_depth(), Some(convention.clear_value()));
+            assert_eq!(opaque.draw_parameters().depth.test, convention.test());
+            assert!(opaque.draw_parameters().depth.write);
+
+            let transparent = RenderState::transparent(convention).draw_parameters();
+            assert_eq!(transparent.depth.test, convention.test());
+            assert!(!transparent.depth.write);
+            assert_eq!(transparent.blend, Blend::alpha_blending());
+
+            let wireframe = RenderState::wireframe(convention);
+            assert_eq!(wireframe.clear_depth(), opaque.clear_depth());
+            assert_eq!(wireframe.draw_parameters().polygon_mode, PolygonMode::Line);
+        }
+        assert_eq!(RenderState::overlay().clear_depth(), None);
+        assert_eq!(
+            RenderState::overlay().draw_parameters().depth.test,
+            DepthTest::Overwrite
+        );
+    }
+
+    #[test]
+    fn new_rejects_inconsistent_depth() {
+        assert!(matches!(
+            RenderState::new(Some(0.0), depth(DepthTest::IfLess, true)),
+            Err(RenderStateError::ClearMismatch { clear: 0.0, .. })
+        ));
+        assert!(matches!(
+            RenderState::new(Some(1.0), depth(DepthTest::IfMoreOrEqual, true)),
+            Err(RenderStateError::ClearMismatch { .. })
+        ));
+        assert!(matches!(
+            RenderState::new(Some(f32::NAN), depth(DepthTest::IfLess, true)),
+            Err(RenderStateError::InvalidClear(_))
+        ));
+        assert!(matches!(
+            RenderState::new(None, depth(DepthTest::Ignore, true)),
+            Err(RenderStateError::WriteIgnored)
+        ));
+    }
+
+    #[test]
+    fn new_accepts_clamped_and_missing_clears() {
+        RenderState::new(Some(2.0), depth(DepthTest::IfLess, true)).unwrap();
+        RenderState::new(Some(-1.0), depth(DepthTest::IfMore, true)).unwrap();
+        RenderState::new(Some(0.5), depth(DepthTest::Overwrite, false)).unwrap();
+        RenderState::new(None, depth(DepthTest::IfLess, false)).unwrap();
+    }
+}
@@ -0,0 +1,449 @@
+use std::{collections::HashMap, path::Path, rc::Rc};
+
+use glium::{
+    IndexBuffer, Program, ProgramCreationError, VertexBuffer,
+    backend::Facade,
+    index::{self, IndexBufferAny, PrimitiveType},
+    vertex::{self, VertexBufferAny},
+};
+
+use crate::{
+    ColorSpace, Preprocessor, SamplerDescriptor, ShaderError, ShaderFile, Texture, TextureLoader,
+    TextureSampler, compile_program,
+    hot_reload::{ProgramFiles, ShaderWatcher},
+    linear_program,
+};
+
+/// GPU objects created once in `Drawable::init` and looked up by name while drawing.
+pub struct Resources {
+    programs: HashMap<&'static str, Program>,
+    vertex_buffers: HashMap<&'static str, VertexBufferAny>,
+    index_buffers: HashMap<&'static str, IndexBufferAny>,
+    textures: HashMap<&'static str, Rc<Texture>>,
+    samplers: HashMap<&'static str, SamplerDescriptor>,
+    texture_loader: TextureLoader,
+    preprocessor: Preprocessor,
+    /// Set when shaders are hot-reloaded.
+    watcher: Option<ShaderWatcher>,
+    compilations: usize,
+}
+
+impl Default for Resources {
+    /// Textures are looked up from the `common` crate's directory up to the workspace root, where
+    /// the shared lesson textures live. A scene with textures of its own can point
+    /// [`texture_loader_mut`](Self::texture_loader_mut) at its crate in `Drawable::init`.
+    fn default() -> Self {
+        Self {
+            programs: HashMap::new(),
+            vertex_buffers: HashMap::new(),
+            index_buffers: HashMap::new(),
+            textures: HashMap::new(),
+            samplers: HashMap::new(),
+            texture_loader: TextureLoader::new(env!("CARGO_MANIFEST_DIR")),
+            preprocessor: Preprocessor::default(),
+            watcher: None,
+            compilations: 0,
+        }
+    }
+}
+
+impl Resources {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Compiles the program unless one is already cached under `name`.
+    pub fn load_program<F: Facade>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        vertex_shader: &str,
+        fragment_shader: &str,
+    ) -> Result<&Program, ProgramCreationError> {
+        if !self.programs.contains_key(name) {
+            let program = linear_program(facade, vertex_shader, fragment_shader)?;
+            self.compilations += 1;
+            self.programs.insert(name, program);
+        }
+        Ok(&self.programs[name])
+    }
+
+    /// Like [`load_program`](Self::load_program), running the sources through the
+    /// [`Preprocessor`] first. With hot-reloading enabled the files are read from disk instead,
+    /// and the program is recompiled when they or their includes change.
+    pub fn load_program_files<F: Facade>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        vertex_shader: ShaderFile,
+        fragment_shader: ShaderFile,
+    ) -> Result<&Program, ShaderError> {
+        self.load_program_variant(facade, name, vertex_shader, fragment_shader, &[])
+    }
+
+    /// Like [`load_program_files`](Self::load_program_files) with extra `#define`s, for
+    /// compiling several permutations of the same shaders under different names.
+    pub fn load_program_variant<F: Facade>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        vertex_shader: ShaderFile,
+        fragment_shader: ShaderFile,
+        defines: &[(&str, &str)],
+    ) -> Result<&Program, ShaderError> {
+        if !self.programs.contains_key(name) {
+            let files = ProgramFiles {
+                vertex: vertex_shader,
+                fragment: fragment_shader,
+                defines: defines
+                    .iter()
+                    .map(|&(define, value)| (define.to_owned(), value.to_owned()))
+                    .collect(),
+            };
+            let from_disk = self.watcher.is_some();
+            let (program, sources) = build(facade, &self.preprocessor, &files, from_disk);
+            if let Some(watcher) = &mut self.watcher {
+                watcher.watch(name, files, &sources);
+            }
+            let program = program?;
+            self.compilations += 1;
+            self.programs.insert(name, program);
+        }
+        Ok(&self.programs[name])
+    }
+
+    pub fn preprocessor(&self) -> &Preprocessor {
+        &self.preprocessor
+    }
+
+    /// For registering includes and defines before the programs using them are loaded.
+    pub fn preprocessor_mut(&mut self) -> &mut Preprocessor {
+        &mut self.preprocessor
+    }
+
+    /// Makes programs loaded from now on through
+    /// [`load_program_files`](Self::load_program_files) hot-reloadable.
+    pub fn enable_hot_reload(&mut self) {
+        self.watcher.get_or_insert_with(ShaderWatcher::default);
+    }
+
+    pub fn hot_reload_enabled(&self) -> bool {
+        self.watcher.is_some()
+    }
+
+    /// Recompiles hot-reloadable programs whose files changed. A program that fails to
+    /// compile is reported and the previous one kept, its files are still watched so that
+    /// fixing an include it just started using triggers the next reload.
+    pub fn reload_programs<F: Facade>(&mut self, facade: &F) {
+        let Some(watcher) = &mut self.watcher else {
+            return;
+        };
+        for name in watcher.poll() {
+            let Some(files) = watcher.files(name).cloned() else {
+                continue;
+            };
+            let (program, sources) = build(facade, &self.preprocessor, &files, true);
+            watcher.watch(name, files, &sources);
+            match program {
+                Ok(program) => {
+                    self.compilations += 1;
+                    self.programs.insert(name, program);
+                    eprintln!("reloaded program `{name}`");
+                }
+                Err(err) => {
+                    eprintln!("failed to reload program `{name}`, keeping the previous one: {err}")
+                }
+            }
+        }
+    }
+
+    pub fn load_vertex_buffer<F, V>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        data: &[V],
+    ) -> Result<&VertexBufferAny, vertex::BufferCreationError>
+    where
+        F: Facade,
+        V: vertex::Vertex + Send + 'static,
+    {
+        if !self.vertex_buffers.contains_key(name) {
+            let buffer = VertexBuffer::new(facade, data)?;
+            self.vertex_buffers.insert(name, buffer.into());
+        }
+        Ok(&self.vertex_buffers[name])
+    }
+
+    pub fn load_index_buffer<F, I>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        primitive: PrimitiveType,
+        data: &[I],
+    ) -> Result<&IndexBufferAny, index::BufferCreationError>
+    where
+        F: Facade,
+        I: index::Index,
+    {
+        if !self.index_buffers.contains_key(name) {
+            let buffer = IndexBuffer::new(facade, primitive, data)?;
+            self.index_buffers.insert(name, buffer.into());
+        }
+        Ok(&self.index_buffers[name])
+    }
+
+    pub fn insert_texture(&mut self, name: &'static str, texture: impl Into<Texture>) {
+        self.textures.insert(name, Rc::new(texture.into()));
+    }
+
+    /// Loads the color map at `path` through the [`TextureLoader`] under `name`, as sRGB. A
+    /// path that fails to load is reported and replaced by the missing-texture checkerboard.
+    pub fn load_texture<F: Facade>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        path: impl AsRef<Path>,
+    ) -> &Texture {
+        self.load_texture_as(facade, name, path, ColorSpace::Srgb)
+    }
+
+    /// Like [`load_texture`](Self::load_texture), for data maps such as normal or roughness
+    /// maps that must not be gamma-decoded.
+    pub fn load_data_texture<F: Facade>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        path: impl AsRef<Path>,
+    ) -> &Texture {
+        self.load_texture_as(facade, name, path, ColorSpace::Linear)
+    }
+
+    fn load_texture_as<F: Facade>(
+        &mut self,
+        facade: &F,
+        name: &'static str,
+        path: impl AsRef<Path>,
+        color_space: ColorSpace,
+    ) -> &Texture {
+        if !self.textures.contains_key(name) {
+            let texture = self
+                .texture_loader
+                .load_or_missing(facade, path, color_space);
+            self.textures.insert(name, texture);
+        }
+        &self.textures[name]
+    }
+
+    /// Sampler settings for the texture `name`, [`SamplerDescriptor::default`] unless set.
+    pub fn set_sampler(&mut self, name: &'static str, descriptor: SamplerDescriptor) {
+        self.samplers.insert(name, descriptor);
+    }
+
+    pub fn texture_loader(&self) -> &TextureLoader {
+        &self.texture_loader
+    }
+
+    pub fn texture_loader_mut(&mut self) -> &mut TextureLoader {
+        &mut self.texture_loader
+    }
+
+    pub fn program(&self, name: &str) -> &Program {
+        self.programs
+            .get(name)
+            .unwrap_or_else(|| panic!("program `{name}` was not loaded"))
+    }
+
+    pub fn vertex_buffer(&self, name: &str) -> &VertexBufferAny {
+        self.vertex_buffers
+            .get(name)
+            .unwrap_or_else(|| panic!("vertex buffer `{name}` was not loaded"))
+    }
+
+    pub fn index_buffer(&self, name: &str) -> &IndexBufferAny {
+        self.index_buffers
+            .get(name)
+            .unwrap_or_else(|| panic!("index buffer `{name}` was not loaded"))
+    }
+
+    pub fn texture(&self, name: &str) -> &Texture {
+        self.textures
+            .get(name)
+            .unwrap_or_else(|| panic!("texture `{name}` was not loaded"))
+    }
+
+    /// The texture `name` wrapped in its configured sampler.
+    pub fn sampler(&self, name: &str) -> TextureSampler<'_> {
+        self.samplers
+            .get(name)
+            .copied()
+            .unwrap_or_default()
+            .sampler(self.texture(name))
+    }
+
+    /// Number of programs actually compiled, cache hits excluded.
+    pub fn program_compilations(&self) -> usize {
+        self.compilations
+    }
+}
+
+/// Preprocesses and compiles a program. Every file it was built from is returned even when
+/// that fails: the two shaders, and the includes of each one that preprocessed.
+fn build<F: Facade>(
+    facade: &F,
+    preprocessor: &Preprocessor,
+    files: &ProgramFiles,
+    from_disk: bool,
+) -> (Result<Program, ShaderError>, Vec<ShaderFile>) {
+    let defines = files.defines();
+    let vertex = preprocessor.process(files.vertex, &defines, from_disk);
+    let fragment = preprocessor.process(files.fragment, &defines, from_disk);
+    let mut sources = vec![files.vertex, files.fragment];
+    for processed in [&vertex, &fragment].into_iter().flatten() {
+        for file in processed.files() {
+            if !sources.contains(file) {
+                sources.push(*file);
+            }
+        }
+    }
+    let program = match (vertex, fragment) {
+        (Ok(vertex), Ok(fragment)) => compile_program(facade, &vertex, &fragment),
+        (Err(err), _) | (_, Err(err)) => Err(err.into()),
+    };
+    (program, sources)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    use glium::{
+        Surface, implement_vertex,
+        index::{NoIndices, PrimitiveType},
+        uniforms::EmptyUniforms,
+    };
+
+    use super::*;
+    use crate::{Drawable, FrameContext, Offscreen, hot_reload::POLL_INTERVAL};
+
+    #[derive(Clone, Copy)]
+    struct Corner {
+        position: [f32; 2],
+    }
+    implement_vertex!(Corner, position);
+
+    const VERTEX_SHADER: &str = "
+        #version 330
+        in vec2 position;
+        void main() {
+            gl_Position = vec4(position, 0.0, 1.0);
+        }
+    ";
+    const FRAGMENT_SHADER: &str = "
+        #version 330
+        out vec4 color;
+        void main() {
+            color = vec4(1.0);
+        }
+    ";
+
+    struct Triangle;
+
+    impl Drawable for Triangle {
+        fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
+            for _ in 0..2 {
+                resources
+                    .load_program(facade, "white", VERTEX_SHADER, FRAGMENT_SHADER)
+                    .unwrap();
+            }
+            let corners =
+                [[-1.0, -1.0], [3.0, -1.0], [-1.0, 3.0]].map(|position| Corner { position });
+            resources
+                .load_vertex_buffer(facade, "triangle", &corners)
+                .unwrap();
+        }
+
+        fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext) {
+            target.clear_color_and_depth((0.0, 0.0, 0.0, 1.0), 1.0);
+            target
+                .draw(
+                    context.resources.vertex_buffer("triangle"),
+                    NoIndices(PrimitiveType::TrianglesList),
+                    context.resources.program("white"),
+                    &EmptyUniforms,
+                    &Default::default(),
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn programs_compile_once_across_frames() {
+        let mut offscreen = Offscreen::new(Triangle, 16, 16).unwrap();
+        for _ in 0..10 {
+            let frame = offscreen.render();
+            assert_eq!(frame.get_pixel(8, 8).0, [255, 255, 255, 255]);
+        }
+        assert_eq!(offscreen.resources().program_compilations(), 1);
+    }
+
+    /// A shader file under `dir`, leaked for the `'static` path.
+    fn shader_file(dir: &Path, name: &str, source: &'static str) -> ShaderFile {
+        let path = dir.join(name);
+        std::fs::write(&path, source).unwrap();
+        ShaderFile::new(source, path.to_string_lossy().into_owned().leak())
+    }
+
+    /// Sleeps until a rewritten file gets a newer modification time than the last write,
+    /// on file systems with one-second timestamps too, and the watcher polls again.
+    fn wait_for_new_mtime() {
+        std::thread::sleep(Duration::from_millis(1100).max(POLL_INTERVAL * 2));
+    }
+
+    #[test]
+    fn failed_reloads_watch_new_includes() {
+        let nanos = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .subsec_nanos();
+        let dir = std::env::temp_dir().join(format!(
+            "hot-reload-failed-includes-{}-{nanos}",
+            std::process::id()
+        ));
+        std::fs::create_dir_all(&dir).unwrap();
+        let context = crate::headless::context().unwrap();
+        let mut resources = Resources::new();
+        resources.enable_hot_reload();
+        let tint = shader_file(&dir, "tint.glsl", "");
+        resources.preprocessor_mut().add_include("tint.glsl", tint);
+        // `#version` has to be the first line for the preprocessor to keep it first.
+        let vertex = shader_file(&dir, "white.vert", VERTEX_SHADER.trim_start());
+        let fragment = shader_file(&dir, "white.frag", FRAGMENT_SHADER.trim_start());
+        resources
+            .load_program_files(&context, "white", vertex, fragment)
+            .unwrap();
+
+        // Start using an include that does not compile yet.
+        wait_for_new_mtime();
+        std::fs::write(tint.path, "vec4 tint() { return undefined; }").unwrap();
+        std::fs::write(
+            fragment.path,
+            "#version 330
+            #include \"tint.glsl\"
+            out vec4 color;
+            void main() {
+                color = tint();
+            }",
+        )
+        .unwrap();
+        resources.reload_programs(&context);
+        assert_eq!(resources.program_compilations(), 1);
+
+        // Fixing only the include is enough.
+        wait_for_new_mtime();
+        std::fs::write(tint.path, "vec4 tint() { return vec4(1.0); }").unwrap();
+        resources.reload_programs(&context);
+        assert_eq!(resources.program_compilations(), 2);
+
+        let _ = std::fs::remove_dir_all(&dir);
+    }
+}
@@ -0,0 +1,164 @@
+use glium::{
+    Texture2d,
+    texture::SrgbTexture2d,
+    uniforms::{
+        AsUniformValue, MagnifySamplerFilter, MinifySamplerFilter, Sampler, SamplerWrapFunction,
+        UniformValue,
+    },
+};
+
+use crate::Texture;
+
+/// How a texture is sampled: wrapping, filtering and anisotropy.
+///
+/// Mipmaps themselves are generated when the texture is loaded, see [`TextureLoader`]. If a
+/// texture lacks its full chain of [`mip_levels`], mipmapped minification falls back to the
+/// plain filter so the texture stays complete.
+///
+/// [`TextureLoader`]: crate::TextureLoader
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SamplerDescriptor {
+    pub wrap: SamplerWrapFunction,
+    pub min_filter: MinifySamplerFilter,
+    pub mag_filter: MagnifySamplerFilter,
+    /// Maximum anisotropy, `1` disables anisotropic filtering.
+    pub anisotropy: u16,
+}
+
+impl Default for SamplerDescriptor {
+    /// Trilinear filtering with 8x anisotropy, repeating.
+    fn default() -> Self {
+        Self {
+            wrap: SamplerWrapFunction::Repeat,
+            min_filter: MinifySamplerFilter::LinearMipmapLinear,
+            mag_filter: MagnifySamplerFilter::Linear,
+            anisotropy: 8,
+        }
+    }
+}
+
+impl SamplerDescriptor {
+    /// Unfiltered, for pixel art and lookup tables.
+    pub fn nearest() -> Self {
+        Self {
+            min_filter: MinifySamplerFilter::Nearest,
+            mag_filter: MagnifySamplerFilter::Nearest,
+            anisotropy: 1,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_wrap(mut self, wrap: SamplerWrapFunction) -> Self {
+        self.wrap = wrap;
+        self
+    }
+
+    pub fn with_anisotropy(mut self, anisotropy: u16) -> Self {
+        self.anisotropy = anisotropy.max(1);
+        self
+    }
+
+    pub fn sampler<'t>(&self, texture: &'t Texture) -> TextureSampler<'t> {
+        let (width, height) = texture.dimensions();
+        let mipmapped = texture.mipmap_levels() >= mip_levels(width, height);
+        match texture {
+            Texture::Linear(texture) => {
+                TextureSampler::Linear(self.configure(Sampler::new(texture), mipmapped))
+            }
+            Texture::Srgb(texture) => {
+                TextureSampler::Srgb(self.configure(Sampler::new(texture), mipmapped))
+            }
+        }
+    }
+
+    fn configure<'t, T>(&self, sampler: Sampler<'t, T>, mipmapped: bool) -> Sampler<'t, T> {
+        let min_filter = if mipmapped {
+            self.min_filter
+        } else {
+            without_mipmaps(self.min_filter)
+        };
+        sampler
+            .wrap_function(self.wrap)
+            .minify_filter(min_filter)
+            .magnify_filter(self.mag_filter)
+            .anisotropy(self.anisotropy)
+    }
+}
+
+/// A [`Texture`] bound with its sampler settings, usable directly in `uniform!`. sRGB
+/// textures are decoded to linear by the hardware when sampled.
+#[derive(Clone, Copy)]
+pub enum TextureSampler<'t> {
+    Linear(Sampler<'t, Texture2d>),
+    Srgb(Sampler<'t, SrgbTexture2d>),
+}
+
+impl<'t> TextureSampler<'t> {
+    /// Like `as_uniform_value`, but borrowing the texture rather than the sampler.
+    pub fn uniform_value(self) -> UniformValue<'t> {
+        match self {
+            Self::Linear(Sampler(texture, behavior)) => {
+                UniformValue::Texture2d(texture, Some(behavior))
+            }
+            Self::Srgb(Sampler(texture, behavior)) => {
+                UniformValue::SrgbTexture2d(texture, Some(behavior))
+            }
+        }
+    }
+}
+
+impl AsUniformValue for TextureSampler<'_> {
+    fn as_uniform_value(&self) -> UniformValue<'_> {
+        self.uniform_value()
+    }
+}
+
+fn without_mipmaps(filter: MinifySamplerFilter) -> MinifySamplerFilter {
+    match filter {
+        MinifySamplerFilter::NearestMipmapNearest | MinifySamplerFilter::NearestMipmapLinear => {
+            MinifySamplerFilter::Nearest
+        }
+        MinifySamplerFilter::LinearMipmapNearest | MinifySamplerFilter::LinearMipmapLinear => {
+            MinifySamplerFilter::Linear
+        }
+        filter => filter,
+    }
+}
+
+/// Number of levels in a full mipmap chain, down to 1x1. Non-power-of-two sizes round down
+/// at each level, as OpenGL does.
+pub fn mip_levels(width: u32, height: u32) -> u32 {
+    32 - width.max(height).max(1).leading_zeros()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn mip_levels_round_down_like_opengl() {
+        // 300x200, 150x100, 75x50, 37x25, 18x12, 9x6, 4x3, 2x1, 1x1.
+        assert_eq!(mip_levels(300, 200), 9);
+        assert_eq!(mip_levels(256, 256), 9);
+        assert_eq!(mip_levels(257, 3), 9);
+        assert_eq!(mip_levels(1024, 1), 11);
+        assert_eq!(mip_levels(1, 1), 1);
+        assert_eq!(mip_levels(0, 0), 1);
+    }
+
+    #[test]
+    fn without_mipmaps_keeps_the_base_filter() {
+        assert_eq!(
+            without_mipmaps(MinifySamplerFilter::LinearMipmapLinear),
+            MinifySamplerFilter::Linear
+        );
+        assert_eq!(
+            without_mipmaps(MinifySamplerFilter::NearestMipmapLinear),
+            MinifySamplerFilter::Nearest
+        );
+        assert_eq!(
+            without_mipmaps(MinifySamplerFilter::Linear),
+            MinifySamplerFilter::Linear
+        );
+    }
+}
@@ -0,0 +1,46 @@
+// Blinn-Phong shading for the lights bound by the `lighting` module. `MAX_LIGHTS` is
+// defined by the preprocessor.
+#pragma once
+
+struct DirectionalLight {
+    vec3 direction;
+    vec3 color;
+};
+
+struct PointLight {
+    vec3 position;
+    vec3 color;
+    float range;
+};
+
+struct SpotLight {
+    vec3 position;
+    vec3 direction;
+    vec3 color;
+    float range;
+    float inner_cos;
+    float outer_cos;
+};
+
+uniform vec3 ambient;
+uniform float shininess;
+uniform int directional_count;
+uniform DirectionalLight directional_lights[MAX_LIGHTS];
+uniform int point_count;
+uniform PointLight point_lights[MAX_LIGHTS];
+uniform int spot_count;
+uniform SpotLight spot_lights[MAX_LIGHTS];
+
+// Diffuse plus specular for light arriving from `to_light`, both unit vectors.
+vec3 blinn_phong(vec3 albedo, vec3 normal, vec3 to_eye, vec3 to_light, vec3 light) {
+    float diffuse = max(dot(normal, to_light), 0.0);
+    vec3 halfway = normalize(to_light + to_eye);
+    float specular = diffuse > 0.0 ? pow(max(dot(normal, halfway), 0.0), shininess) : 0.0;
+    return (albedo * diffuse + vec3(specular)) * light;
+}
+
+// Inverse square falloff, windowed so it reaches zero at `range`.
+float attenuation(float distance, float range) {
+    float window = clamp(1.0 - pow(distance / range, 4.0), 0.0, 1.0);
+    return window * window / (distance * distance + 1.0);
+}
\ No newline at end of file
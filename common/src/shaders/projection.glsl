@@ -0,0 +1,8 @@
+// The model-view-projection matrix shared by the lessons' vertex shaders.
+#pragma once
+
+uniform mat4 transform;
+
+vec4 project(vec3 position) {
+    return transform * vec4(position, 1.0);
+}
\ No newline at end of file
@@ -0,0 +1,401 @@
+use std::{
+    collections::HashMap,
+    fmt, fs,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
+
+use glium::{
+    Texture2d,
+    backend::Facade,
+    texture::{MipmapsOption, RawImage2d, SrgbTexture2d, TextureCreationError},
+};
+use image::{Rgba, RgbaImage};
+
+#[derive(Debug)]
+pub enum TextureError {
+    /// The path exists under none of the searched directories.
+    NotFound {
+        path: PathBuf,
+        searched: Vec<PathBuf>,
+    },
+    Decode {
+        path: PathBuf,
+        source: image::ImageError,
+    },
+    Upload(TextureCreationError),
+}
+
+impl fmt::Display for TextureError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::NotFound { path, searched } => {
+                write!(f, "texture {} not found, searched:", path.display())?;
+                for dir in searched {
+                    write!(f, " {}", dir.display())?;
+                }
+                Ok(())
+            }
+            Self::Decode { path, source } => {
+                write!(f, "failed to decode texture {}: {source}", path.display())
+            }
+            Self::Upload(err) => write!(f, "failed to upload texture: {err}"),
+        }
+    }
+}
+
+impl std::error::Error for TextureError {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Self::NotFound { .. } => None,
+            Self::Decode { source, .. } => Some(source),
+            Self::Upload(err) => Some(err),
+        }
+    }
+}
+
+/// How the texels of an image are encoded.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum ColorSpace {
+    /// Color maps such as albedo, stored gamma-encoded and decoded to linear by the sampler.
+    Srgb,
+    /// Data maps such as normals or roughness, sampled as stored.
+    Linear,
+}
+
+/// A 2D texture in either color space.
+pub enum Texture {
+    Linear(Texture2d),
+    Srgb(SrgbTexture2d),
+}
+
+impl Texture {
+    pub fn color_space(&self) -> ColorSpace {
+        match self {
+            Self::Linear(_) => ColorSpace::Linear,
+            Self::Srgb(_) => ColorSpace::Srgb,
+        }
+    }
+
+    pub fn dimensions(&self) -> (u32, u32) {
+        match self {
+            Self::Linear(texture) => texture.dimensions(),
+            Self::Srgb(texture) => texture.dimensions(),
+        }
+    }
+
+    pub fn mipmap_levels(&self) -> u32 {
+        match self {
+            Self::Linear(texture) => texture.get_mipmap_levels(),
+            Self::Srgb(texture) => texture.get_mipmap_levels(),
+        }
+    }
+}
+
+impl From<Texture2d> for Texture {
+    fn from(texture: Texture2d) -> Self {
+        Self::Linear(texture)
+    }
+}
+
+impl From<SrgbTexture2d> for Texture {
+    fn from(texture: SrgbTexture2d) -> Self {
+        Self::Srgb(texture)
+    }
+}
+
+/// Loads textures relative to an asset root and caches them by resolved path.
+///
+/// Relative paths are looked up in the root and then in each of its parent directories up to
+/// the workspace root, so assets shared there are found from any crate. Crates pass their
+/// manifest directory as the root, which keeps lookups independent of the working directory:
+///
+/// ```ignore
+/// let loader = TextureLoader::new(env!("CARGO_MANIFEST_DIR"));
+/// ```
+///
+/// The same file loaded in both color spaces is uploaded twice.
+pub struct TextureLoader {
+    root: PathBuf,
+    cache: HashMap<(PathBuf, ColorSpace), Rc<Texture>>,
+    missing: Option<Rc<Texture>>,
+    mipmaps: bool,
+}
+
+impl TextureLoader {
+    pub fn new(root: impl Into<PathBuf>) -> Self {
+        Self {
+            root: root.into(),
+            cache: HashMap::new(),
+            missing: None,
+            mipmaps: true,
+        }
+    }
+
+    pub fn root(&self) -> &Path {
+        &self.root
+    }
+
+    pub fn set_root(&mut self, root: impl Into<PathBuf>) {
+        self.root = root.into();
+    }
+
+    /// Whether textures loaded from now on get a generated mipmap chain, on by default.
+    pub fn set_mipmaps(&mut self, mipmaps: bool) {
+        self.mipmaps = mipmaps;
+    }
+
+    pub fn resolve(&self, path: impl AsRef<Path>) -> Result<PathBuf, TextureError> {
+        let path = path.as_ref();
+        if path.is_absolute() {
+            return if path.is_file() {
+                Ok(path.to_owned())
+            } else {
+                Err(TextureError::NotFound {
+                    path: path.to_owned(),
+                    searched: Vec::new(),
+                })
+            };
+        }
+        let searched = self.search_dirs();
+        searched
+            .iter()
+            .map(|dir| dir.join(path))
+            .find(|candidate| candidate.is_file())
+            .ok_or_else(|| TextureError::NotFound {
+                path: path.to_owned(),
+                searched: searched.iter().map(|dir| dir.to_path_buf()).collect(),
+            })
+    }
+
+    /// The root and its ancestors up to the workspace root, or only the root outside a
+    /// workspace.
+    fn search_dirs(&self) -> Vec<&Path> {
+        let mut dirs = Vec::new();
+        for dir in self.root.ancestors() {
+            dirs.push(dir);
+            if is_workspace_root(dir) {
+                return dirs;
+            }
+        }
+        vec![self.root.as_path()]
+    }
+
+    pub fn load_image(&self, path: impl AsRef<Path>) -> Result<RgbaImage, TextureError> {
+        let path = self.resolve(path)?;
+        image::open(&path)
+            .map(|image| image.to_rgba8())
+            .map_err(|source| TextureError::Decode { path, source })
+    }
+
+    /// Uploads the image at `path`, or returns the texture already loaded from it.
+    pub fn load<F: Facade>(
+        &mut self,
+        facade: &F,
+        path: impl AsRef<Path>,
+        color_space: ColorSpace,
+    ) -> Result<Rc<Texture>, TextureError> {
+        let key = (self.resolve(path)?, color_space);
+        if let Some(texture) = self.cache.get(&key) {
+            return Ok(texture.clone());
+        }
+        let image = self.load_image(&key.0)?;
+        let texture = Rc::new(upload(facade, image, color_space, self.mipmaps)?);
+        self.cache.insert(key, texture.clone());
+        Ok(texture)
+    }
+
+    /// Like [`load`](Self::load), but reports the error and returns a checkerboard instead.
+    pub fn load_or_missing<F: Facade>(
+        &mut self,
+        facade: &F,
+        path: impl AsRef<Path>,
+        color_space: ColorSpace,
+    ) -> Rc<Texture> {
+        match self.load(facade, path, color_space) {
+            Ok(texture) => texture,
+            Err(err) => {
+                eprintln!("{err}");
+                self.missing
+                    .get_or_insert_with(|| {
+                        Rc::new(
+                            upload(facade, missing_image(), ColorSpace::Srgb, false)
+                                .expect("failed to upload the missing texture"),
+                        )
+                    })
+                    .clone()
+            }
+        }
+    }
+
+    pub fn cached(&self) -> usize {
+        self.cache.len()
+    }
+}
+
+fn is_workspace_root(dir: &Path) -> bool {
+    fs::read_to_string(dir.join("Cargo.toml"))
+        .is_ok_and(|manifest| manifest.lines().any(|line| line.trim() == "[workspace]"))
+}
+
+/// Magenta and black checkerboard shown in place of textures that failed to load.
+pub fn missing_image() -> RgbaImage {
+    RgbaImage::from_fn(64, 64, |x, y| {
+        if (x / 8 + y / 8) % 2 == 0 {
+            Rgba([255, 0, 255, 255])
+        } else {
+            Rgba([0, 0, 0, 255])
+        }
+    })
+}
+
+/// Uploads with the first row at the bottom, matching `v = 0` at the bottom of the texture.
+fn upload<F: Facade>(
+    facade: &F,
+    image: RgbaImage,
+    color_space: ColorSpace,
+    mipmaps: bool,
+) -> Result<Texture, TextureError> {
+    let dimensions = image.dimensions();
+    let raw = RawImage2d::from_raw_rgba_reversed(&image.into_raw(), dimensions);
+    let mipmaps = if mipmaps {
+        MipmapsOption::AutoGeneratedMipmaps
+    } else {
+        MipmapsOption::NoMipmap
+    };
+    let texture = match color_space {
+        ColorSpace::Srgb => SrgbTexture2d::with_mipmaps(facade, raw, mipmaps).map(Texture::Srgb),
+        ColorSpace::Linear => Texture2d::with_mipmaps(facade, raw, mipmaps).map(Texture::Linear),
+    };
+    texture.map_err(TextureError::Upload)
+}
+
+#[cfg(test)]
+mod tests {
+    use glium::{
+        Surface,
+        framebuffer::SimpleFrameBuffer,
+        index::{NoIndices, PrimitiveType},
+        uniform,
+        vertex::EmptyVertexAttributes,
+    };
+
+    use super::*;
+    use crate::{SamplerDescriptor, headless::Headless, linear_program};
+
+    /// A scratch directory tree under the system temp dir, removed on drop.
+    struct Tree(PathBuf);
+
+    impl Tree {
+        fn new(name: &str) -> Self {
+            let dir = std::env::temp_dir().join(format!("{name}-{}", std::process::id()));
+            let _ = fs::remove_dir_all(&dir);
+            fs::create_dir_all(&dir).unwrap();
+            Self(dir)
+        }
+
+        fn file(&self, path: &str, contents: &str) -> PathBuf {
+            let path = self.0.join(path);
+            fs::create_dir_all(path.parent().unwrap()).unwrap();
+            fs::write(&path, contents).unwrap();
+            path
+        }
+    }
+
+    impl Drop for Tree {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn resolve_searches_up_to_the_workspace_root() {
+        let tree = Tree::new("texture-loader-workspace");
+        tree.file("outside/textures/a.png", "");
+        let workspace = tree.0.join("outside/workspace");
+        tree.file(
+            "outside/workspace/Cargo.toml",
+            "[workspace]\nmembers = [\"lesson\"]\n",
+        );
+        let shared = tree.file("outside/workspace/textures/b.png", "");
+        let own = tree.file("outside/workspace/lesson/textures/b.png", "");
+
+        let loader = TextureLoader::new(workspace.join("lesson"));
+        assert_eq!(loader.resolve("textures/b.png").unwrap(), own);
+        fs::remove_file(&own).unwrap();
+        assert_eq!(loader.resolve("textures/b.png").unwrap(), shared);
+
+        let Err(TextureError::NotFound { searched, .. }) = loader.resolve("textures/a.png") else {
+            panic!("found a file outside the workspace");
+        };
+        assert_eq!(searched, [workspace.join("lesson"), workspace]);
+    }
+
+    #[test]
+    fn uploads_generate_the_full_mipmap_chain() {
+        let context = crate::headless::context().unwrap();
+        let image = RgbaImage::new(300, 200);
+        let mipmapped = upload(&context, image.clone(), ColorSpace::Srgb, true).unwrap();
+        assert_eq!(mipmapped.dimensions(), (300, 200));
+        assert_eq!(mipmapped.mipmap_levels(), crate::mip_levels(300, 200));
+        let single = upload(&context, image, ColorSpace::Linear, false).unwrap();
+        assert_eq!(single.mipmap_levels(), 1);
+    }
+
+    /// Fills the target with a mid-grey sRGB texture through a full-screen triangle.
+    fn draw_mid_grey(facade: &Headless, target: &mut SimpleFrameBuffer) {
+        let image = RgbaImage::from_pixel(4, 4, Rgba([128, 128, 128, 255]));
+        let texture = upload(facade, image, ColorSpace::Srgb, false).unwrap();
+        let program = linear_program(
+            facade,
+            "#version 330
+            out vec2 tex_coord;
+            void main() {
+                vec2 corner = vec2(gl_VertexID & 1, gl_VertexID >> 1) * 2.0;
+                tex_coord = corner;
+                gl_Position = vec4(corner * 2.0 - 1.0, 0.0, 1.0);
+            }",
+            "#version 330
+            uniform sampler2D tex;
+            in vec2 tex_coord;
+            out vec4 color;
+            void main() {
+                color = texture(tex, tex_coord);
+            }",
+        )
+        .unwrap();
+        target
+            .draw(
+                EmptyVertexAttributes { len: 3 },
+                NoIndices(PrimitiveType::TrianglesList),
+                &program,
+                &uniform! { tex: SamplerDescriptor::default().sampler(&texture) },
+                &Default::default(),
+            )
+            .unwrap();
+    }
+
+    #[test]
+    fn srgb_textures_round_trip_through_an_srgb_target() {
+        let frame = crate::headless::render_srgb_frame(4, 4, draw_mid_grey).unwrap();
+        for channel in &frame.get_pixel(2, 2).0[..3] {
+            assert!(channel.abs_diff(128) <= 1, "{:?}", frame.get_pixel(2, 2));
+        }
+        // Decoded to linear and written as is, mid-grey is much darker.
+        let frame = crate::headless::render_frame(4, 4, draw_mid_grey).unwrap();
+        for channel in &frame.get_pixel(2, 2).0[..3] {
+            assert!(channel.abs_diff(55) <= 1, "{:?}", frame.get_pixel(2, 2));
+        }
+    }
+
+    #[test]
+    fn resolve_outside_a_workspace_only_searches_the_root() {
+        let tree = Tree::new("texture-loader-standalone");
+        tree.file("textures/a.png", "");
+        let loader = TextureLoader::new(tree.0.join("assets"));
+        let Err(TextureError::NotFound { searched, .. }) = loader.resolve("textures/a.png") else {
+            panic!("searched above the root");
+        };
+        assert_eq!(searched, [tree.0.join("assets")]);
+    }
+}
@@ -0,0 +1,83 @@
+use std::time::Duration;
+
+/// Longest frame the window and offscreen hosts feed into their [`Timestep`].
+pub(crate) const MAX_FRAME_DELTA: Duration = Duration::from_millis(250);
+
+/// Accumulator for running simulation at a fixed rate independent of the frame rate.
+pub struct Timestep {
+    step: Duration,
+    accumulator: Duration,
+    max_delta: Option<Duration>,
+}
+
+impl Timestep {
+    /// # Panics
+    ///
+    /// If `step` is zero, which would never drain the accumulator.
+    pub fn new(step: Duration) -> Self {
+        assert!(!step.is_zero(), "timestep must be longer than zero");
+        Self {
+            step,
+            accumulator: Duration::ZERO,
+            max_delta: None,
+        }
+    }
+
+    /// Clamps long frames (e.g. after a breakpoint or a window drag) so the simulation doesn't
+    /// have to catch up with hundreds of updates at once.
+    pub fn with_max_delta(mut self, max_delta: Duration) -> Self {
+        self.max_delta = Some(max_delta);
+        self
+    }
+
+    pub fn step(&self) -> Duration {
+        self.step
+    }
+
+    /// Feeds the frame delta and returns how many updates of `step` are due.
+    pub fn advance(&mut self, delta: Duration) -> u32 {
+        let delta = match self.max_delta {
+            Some(max_delta) => delta.min(max_delta),
+            None => delta,
+        };
+        self.accumulator += delta;
+        let mut steps = 0;
+        while self.accumulator >= self.step {
+            self.accumulator -= self.step;
+            steps += 1;
+        }
+        steps
+    }
+
+    /// How far the leftover time has progressed towards the next update, in `[0, 1)`.
+    pub fn alpha(&self) -> f32 {
+        self.accumulator.as_secs_f32() / self.step.as_secs_f32()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advance_counts_whole_steps_and_keeps_the_rest() {
+        let mut timestep = Timestep::new(Duration::from_millis(10));
+        assert_eq!(timestep.advance(Duration::from_millis(25)), 2);
+        assert!((timestep.alpha() - 0.5).abs() < 1e-6);
+        assert_eq!(timestep.advance(Duration::from_millis(5)), 1);
+        assert_eq!(timestep.alpha(), 0.0);
+    }
+
+    #[test]
+    fn max_delta_limits_catching_up() {
+        let mut timestep =
+            Timestep::new(Duration::from_millis(10)).with_max_delta(Duration::from_millis(50));
+        assert_eq!(timestep.advance(Duration::from_secs(5)), 5);
+    }
+
+    #[test]
+    #[should_panic(expected = "timestep must be longer than zero")]
+    fn zero_step_is_rejected() {
+        Timestep::new(Duration::ZERO);
+    }
+}
@@ -0,0 +1,89 @@
+/// Local translation, rotation and scale, combined as `translate3 * rotation * scale3`.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Transform {
+    pub translation: [f32; 3],
+    /// Unit quaternion `[x, y, z, w]`.
+    pub rotation: [f32; 4],
+    pub scale: [f32; 3],
+}
+
+impl Default for Transform {
+    fn default() -> Self {
+        Self {
+            translation: [0.0; 3],
+            rotation: [0.0, 0.0, 0.0, 1.0],
+            scale: [1.0; 3],
+        }
+    }
+}
+
+impl Transform {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn from_translation(translation: [f32; 3]) -> Self {
+        Self {
+            translation,
+            ..Self::default()
+        }
+    }
+
+    pub fn with_rotation(mut self, axis: [f32; 3], degrees: f32) -> Self {
+        self.rotation = quaternion(axis, degrees);
+        self
+    }
+
+    pub fn with_scale(mut self, scale: [f32; 3]) -> Self {
+        self.scale = scale;
+        self
+    }
+
+    /// Rotates further around `axis`, on top of the current rotation.
+    pub fn rotate(&mut self, axis: [f32; 3], degrees: f32) {
+        self.rotation = multiply(quaternion(axis, degrees), self.rotation);
+    }
+
+    pub fn translate(&mut self, offset: [f32; 3]) {
+        for (t, o) in self.translation.iter_mut().zip(offset) {
+            *t += o;
+        }
+    }
+
+    pub fn matrix(&self) -> mats::Mat4<f32> {
+        let [x, y, z, w] = self.rotation;
+        let angle = 2.0 * w.clamp(-1.0, 1.0).acos();
+        let sin = (1.0 - w * w).max(0.0).sqrt();
+        let rotation = if sin < 1e-6 {
+            mats::rotate3(mats::radian(0.0), [0.0, 1.0, 0.0].into())
+        } else {
+            mats::rotate3(
+                mats::radian(angle.to_degrees()),
+                [x / sin, y / sin, z / sin].into(),
+            )
+        };
+        mats::translate3(self.translation.into()) * rotation * mats::scale3(self.scale.into())
+    }
+}
+
+fn quaternion(axis: [f32; 3], degrees: f32) -> [f32; 4] {
+    let length = (axis[0] * axis[0] + axis[1] * axis[1] + axis[2] * axis[2]).sqrt();
+    if length == 0.0 {
+        return [0.0, 0.0, 0.0, 1.0];
+    }
+    let (sin, cos) = (degrees.to_radians() / 2.0).sin_cos();
+    let s = sin / length;
+    [axis[0] * s, axis[1] * s, axis[2] * s, cos]
+}
+
+/// Hamilton product, `a * b` applies `b` first.
+fn multiply(a: [f32; 4], b: [f32; 4]) -> [f32; 4] {
+    let [ax, ay, az, aw] = a;
+    let [bx, by, bz, bw] = b;
+    [
+        aw * bx + ax * bw + ay * bz - az * by,
+        aw * by - ax * bz + ay * bw + az * bx,
+        aw * bz + ax * by - ay * bx + az * bw,
+        aw * bw - ax * bx - ay * by - az * bz,
+    ]
+}
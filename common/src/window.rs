@@ -0,0 +1,400 @@
+use std::{
+    error::Error,
+    path::Path,
+    time::{Duration, Instant},
+};
+
+use glium::{
+    BlitTarget, Display, Frame, Surface,
+    backend::Facade,
+    framebuffer::SimpleFrameBuffer,
+    glutin::surface::WindowSurface,
+    texture::{MipmapsOption, SrgbFormat, SrgbTexture2d},
+    uniforms::MagnifySamplerFilter,
+    winit::{
+        application::ApplicationHandler,
+        event::{DeviceEvent, DeviceId, WindowEvent},
+        event_loop::{ActiveEventLoop, ControlFlow},
+        window::Window,
+    },
+};
+use image::RgbaImage;
+
+use crate::{
+    Capture, Clock, InputState, RealClock, Resources, Timestep, capture::Recording, ecs::World,
+    headless::to_rgba_image, hot_reload, timestep::MAX_FRAME_DELTA,
+};
+
+pub struct FrameContext<'a> {
+    pub size: (u32, u32),
+    /// Time since the previous frame.
+    pub delta: Duration,
+    /// Time since the first frame.
+    pub elapsed: Duration,
+    /// Progress between the last update and the next one, for interpolating simulated state.
+    pub alpha: f32,
+    pub resources: &'a Resources,
+}
+
+impl FrameContext<'_> {
+    pub fn aspect(&self) -> f32 {
+        self.size.0 as f32 / self.size.1 as f32
+    }
+}
+
+pub trait Drawable {
+    fn init<F: Facade>(&mut self, facade: &F, resources: &mut Resources) {
+        let _ = (facade, resources);
+    }
+
+    /// Advances the simulation by one fixed step, called zero or more times before each draw.
+    fn update(&mut self, dt: Duration, input: &InputState) {
+        let _ = (dt, input);
+    }
+
+    fn draw<S: Surface>(&mut self, target: &mut S, context: &FrameContext);
+
+    fn handle(
+        &mut self,
+        window: &Window,
+        event_loop: &glium::winit::event_loop::ActiveEventLoop,
+        window_id: glium::winit::window::WindowId,
+        event: glium::winit::event::WindowEvent,
+    ) {
+        let _ = (window, event_loop, window_id, event);
+    }
+
+    /// Polled after every drawn frame, typically set from a hotkey in `handle`.
+    fn capture(&mut self) -> Option<Capture> {
+        None
+    }
+
+    /// The scene's [`World`], whose behaviors and systems then run on every step right after
+    /// `update`. The scene still draws it.
+    fn world(&mut self) -> Option<&mut World> {
+        None
+    }
+}
+
+/// Runs the updates due after `delta`. Only the first one sees the input gathered since the
+/// previous frame; the per-frame state is cleared after it, so catching up after a slow frame
+/// does not apply a key press or mouse movement several times. Without a due update the input
+/// carries over to the next frame.
+pub fn run_updates<T: Drawable>(
+    impl_: &mut T,
+    timestep: &mut Timestep,
+    delta: Duration,
+    input: &mut InputState,
+) {
+    for step in 0..timestep.advance(delta) {
+        impl_.update(timestep.step(), input);
+        if let Some(world) = impl_.world() {
+            world.update(timestep.step(), input);
+        }
+        if step == 0 {
+            input.end_frame();
+        }
+    }
+}
+
+pub struct MyWindow<T: Drawable, C: Clock = RealClock> {
+    impl_: T,
+    window: Window,
+    display: Display<WindowSurface>,
+    resources: Resources,
+    clock: C,
+    timestep: Timestep,
+    input: InputState,
+    frame_time: Option<Duration>,
+    last_frame: Instant,
+    pending: Option<Capture>,
+    recording: Option<Recording>,
+}
+
+impl<T: Drawable> MyWindow<T> {
+    pub fn new(impl_: T, window: Window, display: Display<WindowSurface>) -> Self {
+        Self::with_clock(impl_, window, display, RealClock::new())
+    }
+}
+
+impl<T: Drawable, C: Clock> MyWindow<T, C> {
+    pub fn with_clock(
+        mut impl_: T,
+        window: Window,
+        display: Display<WindowSurface>,
+        clock: C,
+    ) -> Self {
+        let mut resources = Resources::new();
+        if hot_reload::requested() {
+            resources.enable_hot_reload();
+        }
+        impl_.init(&display, &mut resources);
+        Self {
+            impl_,
+            window,
+            display,
+            resources,
+            clock,
+            timestep: Timestep::new(Duration::from_secs(1) / 60).with_max_delta(MAX_FRAME_DELTA),
+            input: InputState::new(),
+            frame_time: None,
+            last_frame: Instant::now(),
+            pending: None,
+            recording: None,
+        }
+    }
+
+    pub fn window(&self) -> &Window {
+        &self.window
+    }
+
+    pub fn display(&self) -> &Display<WindowSurface> {
+        &self.display
+    }
+
+    pub fn resources(&self) -> &Resources {
+        &self.resources
+    }
+
+    /// Sets the length of one `Drawable::update` step, 60 Hz by default. Panics if `step` is
+    /// zero.
+    pub fn with_timestep(mut self, step: Duration) -> Self {
+        self.timestep = Timestep::new(step).with_max_delta(MAX_FRAME_DELTA);
+        self
+    }
+
+    /// Limits how often frames are drawn, uncapped by default. A rate that is not positive and
+    /// finite leaves the frame rate uncapped.
+    pub fn with_frame_rate_cap(mut self, fps: Option<f32>) -> Self {
+        self.frame_time = fps.and_then(frame_time);
+        if let Some(fps) = fps
+            && self.frame_time.is_none()
+        {
+            eprintln!("ignoring frame-rate cap of {fps} fps");
+        }
+        self
+    }
+
+    pub fn input(&self) -> &InputState {
+        &self.input
+    }
+
+    pub fn clock(&self) -> &C {
+        &self.clock
+    }
+
+    pub fn clock_mut(&mut self) -> &mut C {
+        &mut self.clock
+    }
+
+    /// Queues a capture, taken from the next frame once it is drawn.
+    pub fn capture(&mut self, capture: Capture) {
+        self.pending = Some(capture);
+    }
+
+    /// Copies `frame` into an image. Must happen before the frame is presented, the back buffer
+    /// is undefined after the swap.
+    fn read_frame(&self, frame: &Frame) -> Result<RgbaImage, Box<dyn Error>> {
+        let (width, height) = frame.get_dimensions();
+        let copy = SrgbTexture2d::empty_with_format(
+            &self.display,
+            SrgbFormat::U8U8U8U8,
+            MipmapsOption::NoMipmap,
+            width,
+            height,
+        )?;
+        let target = SimpleFrameBuffer::new(&self.display, &copy)?;
+        let rect = BlitTarget {
+            left: 0,
+            bottom: 0,
+            width: width as i32,
+            height: height as i32,
+        };
+        frame.blit_whole_color_to(&target, &rect, MagnifySamplerFilter::Nearest);
+        Ok(to_rgba_image(copy.read()))
+    }
+
+    fn save_frame(&self, frame: &Frame, path: &Path) -> Result<(), Box<dyn Error>> {
+        self.read_frame(frame)?.save(path)?;
+        Ok(())
+    }
+
+    fn serve_captures(&mut self, frame: &Frame) {
+        match self.pending.take().or_else(|| self.impl_.capture()) {
+            Some(Capture::Screenshot(path)) => {
+                if let Err(err) = self.save_frame(frame, &path) {
+                    eprintln!("failed to save {}: {err}", path.display());
+                }
+            }
+            Some(Capture::Sequence { dir, frames }) => {
+                if let Err(err) = std::fs::create_dir_all(&dir) {
+                    eprintln!("failed to create {}: {err}", dir.display());
+                } else {
+                    self.recording = Some(Recording::new(dir, frames));
+                }
+            }
+            None => {}
+        }
+
+        let Some(path) = self.recording.as_mut().and_then(Recording::next_path) else {
+            self.recording = None;
+            return;
+        };
+        if let Err(err) = self.save_frame(frame, &path) {
+            eprintln!("failed to save {}: {err}", path.display());
+            self.recording = None;
+        }
+    }
+}
+
+/// Time between frames at `fps`, `None` unless `fps` is positive and finite.
+fn frame_time(fps: f32) -> Option<Duration> {
+    if fps.is_finite() && fps > 0.0 {
+        Duration::try_from_secs_f32(fps.recip()).ok()
+    } else {
+        None
+    }
+}
+
+impl<T: Drawable, C: Clock> ApplicationHandler for MyWindow<T, C> {
+    fn resumed(&mut self, _event_loop: &glium::winit::event_loop::ActiveEventLoop) {}
+
+    fn window_event(
+        &mut self,
+        event_loop: &glium::winit::event_loop::ActiveEventLoop,
+        _window_id: glium::winit::window::WindowId,
+        event: glium::winit::event::WindowEvent,
+    ) {
+        if let WindowEvent::CloseRequested = event {
+            event_loop.exit();
+        }
+
+        if let WindowEvent::Resized(size) = event {
+            self.display.resize(size.into());
+        }
+
+        if let WindowEvent::RedrawRequested = event {
+            self.last_frame = Instant::now();
+            let delta = self.clock.tick();
+            run_updates(&mut self.impl_, &mut self.timestep, delta, &mut self.input);
+
+            self.resources.reload_programs(&self.display);
+            let mut target = self.display.draw();
+            let context = FrameContext {
+                size: self.window.inner_size().into(),
+                delta,
+                elapsed: self.clock.elapsed(),
+                alpha: self.timestep.alpha(),
+                resources: &self.resources,
+            };
+            self.impl_.draw(&mut target, &context);
+            self.serve_captures(&target);
+            target.finish().unwrap();
+        }
+
+        self.input.handle_event(&event);
+        self.impl_
+            .handle(&self.window, event_loop, _window_id, event);
+    }
+
+    fn device_event(
+        &mut self,
+        _event_loop: &ActiveEventLoop,
+        _device_id: DeviceId,
+        event: DeviceEvent,
+    ) {
+        if let DeviceEvent::MouseMotion { delta: (dx, dy) } = event {
+            self.input.add_raw_motion(dx, dy);
+        }
+    }
+
+    fn about_to_wait(&mut self, event_loop: &ActiveEventLoop) {
+        let Some(frame_time) = self.frame_time else {
+            self.window.request_redraw();
+            return;
+        };
+        let next_frame = self.last_frame + frame_time;
+        if Instant::now() >= next_frame {
+            self.window.request_redraw();
+        } else {
+            event_loop.set_control_flow(ControlFlow::WaitUntil(next_frame));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use glium::winit::keyboard::KeyCode;
+
+    use super::*;
+
+    /// Counts what its updates see.
+    #[derive(Default)]
+    struct Recorder {
+        updates: u32,
+        presses: u32,
+        mouse_delta: (f64, f64),
+    }
+
+    impl Drawable for Recorder {
+        fn update(&mut self, _dt: Duration, input: &InputState) {
+            self.updates += 1;
+            if input.just_pressed(KeyCode::KeyL) {
+                self.presses += 1;
+            }
+            self.mouse_delta.0 += input.mouse_delta().0;
+            self.mouse_delta.1 += input.mouse_delta().1;
+        }
+
+        fn draw<S: Surface>(&mut self, _target: &mut S, _context: &FrameContext) {}
+    }
+
+    fn frame(recorder: &mut Recorder, timestep: &mut Timestep, input: &mut InputState, steps: u32) {
+        run_updates(recorder, timestep, timestep.step() * steps, input);
+    }
+
+    #[test]
+    fn catching_up_sees_frame_input_once() {
+        let mut recorder = Recorder::default();
+        let mut timestep = Timestep::new(Duration::from_millis(10));
+        let mut input = InputState::new();
+        input.move_cursor(0.0, 0.0);
+        input.press(KeyCode::KeyL);
+        input.move_cursor(3.0, -2.0);
+
+        frame(&mut recorder, &mut timestep, &mut input, 3);
+        assert_eq!(recorder.updates, 3);
+        assert_eq!(recorder.presses, 1);
+        assert_eq!(recorder.mouse_delta, (3.0, -2.0));
+        assert!(input.is_pressed(KeyCode::KeyL));
+        assert!(!input.just_pressed(KeyCode::KeyL));
+    }
+
+    #[test]
+    fn input_waits_for_a_frame_with_an_update() {
+        let mut recorder = Recorder::default();
+        let mut timestep = Timestep::new(Duration::from_millis(10));
+        let mut input = InputState::new();
+        input.press(KeyCode::KeyL);
+        input.scroll_by(0.0, 1.0);
+
+        frame(&mut recorder, &mut timestep, &mut input, 0);
+        assert_eq!(recorder.updates, 0);
+        assert!(input.just_pressed(KeyCode::KeyL));
+        assert_eq!(input.scroll(), (0.0, 1.0));
+
+        frame(&mut recorder, &mut timestep, &mut input, 1);
+        assert_eq!(recorder.presses, 1);
+        assert_eq!(input.scroll(), (0.0, 0.0));
+    }
+
+    #[test]
+    fn frame_time_rejects_unusable_rates() {
+        assert_eq!(frame_time(4.0), Some(Duration::from_millis(250)));
+        assert_eq!(frame_time(0.0), None);
+        assert_eq!(frame_time(-30.0), None);
+        assert_eq!(frame_time(f32::NAN), None);
+        assert_eq!(frame_time(f32::INFINITY), None);
+        assert_eq!(frame_time(f32::MIN_POSITIVE), None);
+    }
+}